@@ -50,6 +50,8 @@ pub struct ProjectData {
     pub versatap: Option<i32>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub channel_alias: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub simulator_rules: Vec<SimulatorRule>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,6 +83,19 @@ pub struct ReceiveCommand {
     pub param6: i32,
 }
 
+/// Automatic answer used when termLight simulates the device: whenever
+/// receive sequence `receive_index` arrives, send sequence `send_index`
+/// after `delay_ms`. Stored as termLight-only SIMULATOR sections.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulatorRule {
+    pub receive_index: i32,
+    pub send_index: i32,
+    pub delay_ms: u64,
+    pub enabled: bool,
+    /// Stop answering after this many answers; 0 answers every match
+    pub max_answers: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecentProject {
     pub name: String,
@@ -110,6 +125,7 @@ pub fn parse_project_file(path: &Path) -> Result<ProjectData, String> {
         receive_commands: vec![],
         versatap: None,
         channel_alias: vec![],
+        simulator_rules: vec![],
     };
     
     while idx < lines.len() {
@@ -141,7 +157,8 @@ pub fn parse_project_file(path: &Path) -> Result<ProjectData, String> {
                 
                 if val_str.starts_with("COMMDISPLAY") || val_str.starts_with("COMMCHANNELS") 
                     || val_str.starts_with("VERSATAP") || val_str.starts_with("CHANNELALIAS")
-                    || val_str.starts_with("SEND") || val_str.starts_with("RECEIVE") {
+                    || val_str.starts_with("SEND") || val_str.starts_with("RECEIVE")
+                    || val_str.starts_with("SIMULATOR") {
                     idx -= 1; // Back up so we process this header next iteration
                     break;
                 }
@@ -174,7 +191,7 @@ pub fn parse_project_file(path: &Path) -> Result<ProjectData, String> {
                 // Stop if we hit an empty line or another section header
                 if val_str.is_empty() || val_str.starts_with("SEND") || val_str.starts_with("RECEIVE") 
                     || val_str.starts_with("VERSION") || val_str.starts_with("COMM") 
-                    || val_str.starts_with("VERSATAP") {
+                    || val_str.starts_with("VERSATAP") || val_str.starts_with("SIMULATOR") {
                     idx -= 1;
                     break;
                 }
@@ -190,7 +207,8 @@ pub fn parse_project_file(path: &Path) -> Result<ProjectData, String> {
                 // Stop if we hit an empty line or another section header
                 if val_str.is_empty() || val_str.starts_with("SEND") || val_str.starts_with("RECEIVE")
                     || val_str.starts_with("VERSION") || val_str.starts_with("COMM") 
-                    || val_str.starts_with("VERSATAP") || val_str.starts_with("CHANNELALIAS") {
+                    || val_str.starts_with("VERSATAP") || val_str.starts_with("CHANNELALIAS")
+                    || val_str.starts_with("SIMULATOR") {
                     idx -= 1;
                     break;
                 }
@@ -209,7 +227,8 @@ pub fn parse_project_file(path: &Path) -> Result<ProjectData, String> {
                         // Skip to next section
                         while idx < lines.len() && !lines[idx].trim().is_empty() 
                             && !lines[idx].trim().starts_with("SEND") 
-                            && !lines[idx].trim().starts_with("RECEIVE") {
+                            && !lines[idx].trim().starts_with("RECEIVE")
+                            && !lines[idx].trim().starts_with("SIMULATOR") {
                             idx += 1;
                         }
                         continue;
@@ -227,7 +246,23 @@ pub fn parse_project_file(path: &Path) -> Result<ProjectData, String> {
                         // Skip to next section
                         while idx < lines.len() && !lines[idx].trim().is_empty() 
                             && !lines[idx].trim().starts_with("SEND") 
-                            && !lines[idx].trim().starts_with("RECEIVE") {
+                            && !lines[idx].trim().starts_with("RECEIVE")
+                            && !lines[idx].trim().starts_with("SIMULATOR") {
+                            idx += 1;
+                        }
+                        continue;
+                    }
+                }
+            }
+        } else if line == "SIMULATOR" {
+            // Parse SIMULATOR rule with error isolation
+            idx += 1;
+            if idx + 4 < lines.len() {
+                match parse_simulator_rule(&lines, &mut idx) {
+                    Ok(rule) => project.simulator_rules.push(rule),
+                    Err(e) => {
+                        eprintln!("Warning: Failed to parse SIMULATOR rule: {}, skipping", e);
+                        while idx < lines.len() && !lines[idx].trim().is_empty() {
                             idx += 1;
                         }
                         continue;
@@ -317,6 +352,31 @@ fn parse_receive_command(lines: &[&str], idx: &mut usize) -> Result<ReceiveComma
     })
 }
 
+// Helper function to parse a SIMULATOR rule
+fn parse_simulator_rule(lines: &[&str], idx: &mut usize) -> Result<SimulatorRule, String> {
+    let receive_index = lines[*idx].trim().parse()
+        .map_err(|e| format!("Invalid SIMULATOR receive index: {}", e))?;
+    *idx += 1;
+    let send_index = lines[*idx].trim().parse()
+        .map_err(|e| format!("Invalid SIMULATOR send index: {}", e))?;
+    *idx += 1;
+    let delay_ms = lines[*idx].trim().parse()
+        .map_err(|e| format!("Invalid SIMULATOR delay: {}", e))?;
+    *idx += 1;
+    let enabled = lines[*idx].trim() != "0";
+    *idx += 1;
+    let max_answers = lines[*idx].trim().parse()
+        .map_err(|e| format!("Invalid SIMULATOR max answers: {}", e))?;
+    
+    Ok(SimulatorRule {
+        receive_index,
+        send_index,
+        delay_ms,
+        enabled,
+        max_answers,
+    })
+}
+
 // ============================================================================
 // Serializer Implementation
 // ============================================================================
@@ -394,6 +454,17 @@ pub fn write_project_file(project: &ProjectData, path: &Path) -> Result<(), Stri
         output.push_str("\r\n");
     }
     
+    // SIMULATOR rules (termLight only)
+    for rule in &project.simulator_rules {
+        output.push_str("SIMULATOR\r\n");
+        output.push_str(&format!("{}\r\n", rule.receive_index));
+        output.push_str(&format!("{}\r\n", rule.send_index));
+        output.push_str(&format!("{}\r\n", rule.delay_ms));
+        output.push_str(&format!("{}\r\n", rule.enabled as i32));
+        output.push_str(&format!("{}\r\n", rule.max_answers));
+        output.push_str("\r\n");
+    }
+    
     fs::write(path, output)
         .map_err(|e| format!("Failed to write file: {}", e))?;
     
@@ -416,7 +487,7 @@ fn get_recent_files_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
 
 /// Ensures the directory for recent projects exists before writing to it.
 /// This implements lazy initialization - directory is only created when needed.
-fn ensure_recent_files_dir(path: &PathBuf) -> Result<(), String> {
+fn ensure_recent_files_dir(path: &Path) -> Result<(), String> {
     if let Some(parent) = path.parent() {
         if !parent.exists() {
             fs::create_dir_all(parent)
@@ -688,4 +759,62 @@ ATM Sof Reset
         assert_eq!(project.send_commands[0].name, "-----------------------");
         assert_eq!(project.send_commands[1].name, "ATM Sof Reset");
     }
+
+    #[test]
+    fn test_simulator_rules_round_trip() {
+        let content = "VERSION
+8
+
+COMMSETTINGS
+0
+COM3
+COM2
+57600
+2
+63
+4
+0
+0
+
+COMMDISPLAY
+0
+
+SEND
+0
+OK
+4F 4B 0D 0A
+0
+5
+
+SIMULATOR
+0
+0
+50
+1
+0
+
+SIMULATOR
+1
+0
+0
+0
+3
+";
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", content).unwrap();
+
+        let project = parse_project_file(file.path()).unwrap();
+        assert_eq!(project.send_commands.len(), 1);
+        assert_eq!(
+            project.simulator_rules,
+            vec![
+                SimulatorRule { receive_index: 0, send_index: 0, delay_ms: 50, enabled: true, max_answers: 0 },
+                SimulatorRule { receive_index: 1, send_index: 0, delay_ms: 0, enabled: false, max_answers: 3 },
+            ]
+        );
+
+        let saved = NamedTempFile::new().unwrap();
+        write_project_file(&project, saved.path()).unwrap();
+        assert_eq!(parse_project_file(saved.path()).unwrap().simulator_rules, project.simulator_rules);
+    }
 }
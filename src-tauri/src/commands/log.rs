@@ -0,0 +1,790 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file log.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use crate::serial::record::{Direction, Record};
+use crate::serial::state::SerialState;
+use chrono::{DateTime, Local, NaiveDateTime};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::State;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Docklight log file representations. Each gets its own file, named like
+/// Docklight's: `<base>_asc.txt`, `<base>_hex.txt`, `<base>_dec.txt`,
+/// `<base>_bin.txt`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Ascii,
+    Hex,
+    Decimal,
+    Binary,
+}
+
+impl LogFormat {
+    fn file_suffix(self) -> &'static str {
+        match self {
+            LogFormat::Ascii => "asc",
+            LogFormat::Hex => "hex",
+            LogFormat::Decimal => "dec",
+            LogFormat::Binary => "bin",
+        }
+    }
+
+    /// The format of a log file, from the suffix in its name
+    /// (`log_hex.txt`, `soak_COM1_3_dec.txt.gz`, ...).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let stem = name.trim_end_matches(".gz").trim_end_matches(".txt");
+        [LogFormat::Ascii, LogFormat::Hex, LogFormat::Decimal, LogFormat::Binary]
+            .into_iter()
+            .find(|format| stem.ends_with(&format!("_{}", format.file_suffix())))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSettings {
+    pub directory: String,
+    #[serde(default = "default_base_name")]
+    pub base_name: String,
+    pub formats: Vec<LogFormat>,
+    /// How often written data is forced to disk. Everything is handed to the
+    /// OS as soon as it is logged, so only a power loss can cost this much.
+    #[serde(default = "default_sync_interval_ms")]
+    pub sync_interval_ms: u64,
+    /// Start a new segment before a file would grow past this many bytes
+    #[serde(default)]
+    pub max_segment_bytes: Option<u64>,
+    /// Start a new segment after this many seconds
+    #[serde(default)]
+    pub max_segment_secs: Option<u64>,
+    /// Compress segments once they are closed
+    #[serde(default)]
+    pub gzip: bool,
+    /// Delete the oldest segments beyond this many, including the open one
+    #[serde(default)]
+    pub max_segments: Option<usize>,
+    /// File name without extension. Placeholders: `{base}`, `{port}`,
+    /// `{timestamp}` (segment start), `{index}` (segment number) and
+    /// `{format}` (asc, hex, dec or bin). Defaults to Docklight's
+    /// `{base}_{format}`, or `{base}_{port}_{timestamp}_{format}` when
+    /// rotating.
+    #[serde(default)]
+    pub name_template: Option<String>,
+}
+
+impl LogSettings {
+    fn rotates(&self) -> bool {
+        self.max_segment_bytes.is_some() || self.max_segment_secs.is_some()
+    }
+}
+
+fn default_base_name() -> String {
+    "log".to_string()
+}
+
+fn default_sync_interval_ms() -> u64 {
+    1000
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogStatus {
+    /// Files of the segment being written
+    pub files: Vec<String>,
+    pub segment_index: u32,
+    pub paused: bool,
+    pub bytes_logged: u64,
+    pub started_at: DateTime<Local>,
+    /// Last I/O error; logging stops unless it happened while rotating, in
+    /// which case the previous segment is kept on
+    pub error: Option<String>,
+}
+
+enum LogMessage {
+    Record(Record, u8),
+    Note(String),
+}
+
+/// A session's running log. Records are formatted and written on a
+/// dedicated thread so a slow disk never stalls the read thread.
+pub struct SessionLog {
+    sender: Option<Sender<LogMessage>>,
+    writer: Option<JoinHandle<()>>,
+    paused: AtomicBool,
+    started_at: DateTime<Local>,
+    progress: Arc<LogProgress>,
+}
+
+/// What the writer thread reports back for `LogStatus`.
+#[derive(Default)]
+struct LogProgress {
+    files: Mutex<Vec<PathBuf>>,
+    segment_index: AtomicU64,
+    bytes_logged: AtomicU64,
+    error: Mutex<Option<String>>,
+}
+
+impl LogProgress {
+    fn set_error(&self, error: String) {
+        println!("Log writer: {}", error);
+        if let Ok(mut current) = self.error.lock() {
+            *current = Some(error);
+        }
+    }
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+const TIMESTAMP_FORMAT: &str = "%d/%m/%Y %H:%M:%S%.3f";
+
+const CONTROL_NAMES: [&str; 32] = [
+    "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT", "LF", "VT", "FF", "CR", "SO", "SI",
+    "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC", "FS", "GS", "RS", "US",
+];
+
+fn format_data(format: LogFormat, data: &[u8]) -> String {
+    match format {
+        LogFormat::Ascii => data
+            .iter()
+            .map(|&b| match b {
+                0x00..=0x1F => format!("<{}>", CONTROL_NAMES[b as usize]),
+                0x7F => "<DEL>".to_string(),
+                _ => char::from(b).to_string(),
+            })
+            .collect(),
+        LogFormat::Hex => join_bytes(data, |b| format!("{:02X}", b)),
+        LogFormat::Decimal => join_bytes(data, |b| format!("{:03}", b)),
+        LogFormat::Binary => join_bytes(data, |b| format!("{:08b}", b)),
+    }
+}
+
+fn join_bytes(data: &[u8], format_byte: impl Fn(u8) -> String) -> String {
+    data.iter().map(|&b| format_byte(b)).collect::<Vec<_>>().join(" ")
+}
+
+fn format_timestamp(time: &DateTime<Local>) -> String {
+    time.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// One log line, e.g. `18/10/2026 14:23:01.123 [TX] - AT<CR>`. Two-port
+/// sessions mark the side: `[RX1]`, `[RX2]`.
+fn format_line(format: LogFormat, record: &Record, channel: u8) -> String {
+    // Markers are written like notes so reading the log back skips them
+    if let Some(marker) = record.marker {
+        return format!("{} {}\r\n", format_timestamp(&record.first_byte.wall_clock), marker.label());
+    }
+    let direction = match record.direction {
+        Direction::Rx => "RX",
+        Direction::Tx => "TX",
+    };
+    let channel = if channel > 0 { channel.to_string() } else { String::new() };
+    format!(
+        "{} [{}{}] - {}\r\n",
+        format_timestamp(&record.first_byte.wall_clock),
+        direction,
+        channel,
+        format_data(format, &record.data)
+    )
+}
+
+/// A frame read back from a log file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedFrame {
+    pub time: NaiveDateTime,
+    pub direction: Direction,
+    pub channel: u8,
+    pub data: Vec<u8>,
+}
+
+/// Parse a line written by `format_line`. Notes and anything else that isn't
+/// a frame give `None`.
+pub fn parse_line(format: LogFormat, line: &str) -> Option<LoggedFrame> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let (time, rest) = line.split_once(" [")?;
+    let (label, data) = rest.split_once("] - ")?;
+    let time = NaiveDateTime::parse_from_str(time, TIMESTAMP_FORMAT).ok()?;
+    let (direction, channel) = if let Some(channel) = label.strip_prefix("RX") {
+        (Direction::Rx, channel)
+    } else {
+        (Direction::Tx, label.strip_prefix("TX")?)
+    };
+    let channel = if channel.is_empty() { 0 } else { channel.parse().ok()? };
+    Some(LoggedFrame { time, direction, channel, data: parse_data(format, data)? })
+}
+
+fn parse_data(format: LogFormat, text: &str) -> Option<Vec<u8>> {
+    let radix = match format {
+        LogFormat::Ascii => return Some(parse_ascii(text)),
+        LogFormat::Hex => 16,
+        LogFormat::Decimal => 10,
+        LogFormat::Binary => 2,
+    };
+    text.split_whitespace()
+        .map(|token| u8::from_str_radix(token, radix).ok())
+        .collect()
+}
+
+/// Undo the `<CR>`-style escapes; any other text is taken byte for byte.
+fn parse_ascii(text: &str) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let escaped = rest.strip_prefix('<').and_then(|after| {
+            let (name, tail) = after.split_once('>')?;
+            let byte = match name {
+                "DEL" => 0x7F,
+                _ => CONTROL_NAMES.iter().position(|&control| control == name)? as u8,
+            };
+            Some((byte, tail))
+        });
+        match escaped {
+            Some((byte, tail)) => {
+                data.push(byte);
+                rest = tail;
+            }
+            None => {
+                data.push(c as u32 as u8);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    data
+}
+
+// ============================================================================
+// Writer
+// ============================================================================
+
+impl SessionLog {
+    pub fn start(settings: &LogSettings, session_id: &str, port_name: &str) -> Result<Self, String> {
+        if settings.formats.is_empty() {
+            return Err("Select at least one log format".to_string());
+        }
+        fs::create_dir_all(&settings.directory)
+            .map_err(|e| format!("Failed to create log directory: {}", e))?;
+
+        let progress = Arc::new(LogProgress::default());
+        let mut output = LogOutput::open(settings.clone(), port_name, progress.clone())?;
+
+        let (sender, receiver) = mpsc::channel();
+        let sync_interval = Duration::from_millis(settings.sync_interval_ms.max(1));
+        let writer = {
+            let progress = progress.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = run_writer(&mut output, receiver, sync_interval, &progress) {
+                    progress.set_error(e);
+                }
+            })
+        };
+
+        let log = Self {
+            sender: Some(sender),
+            writer: Some(writer),
+            paused: AtomicBool::new(false),
+            started_at: Local::now(),
+            progress,
+        };
+        log.note(&format!("Log started - session '{}' on {}", session_id, port_name));
+        Ok(log)
+    }
+
+    pub fn write(&self, record: &Record, channel: u8) {
+        if self.paused.load(Ordering::SeqCst) {
+            return;
+        }
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(LogMessage::Record(record.clone(), channel));
+        }
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        if self.paused.swap(paused, Ordering::SeqCst) != paused {
+            self.note(if paused { "Log paused" } else { "Log resumed" });
+        }
+    }
+
+    pub fn status(&self) -> LogStatus {
+        let files = self.progress.files.lock().map(|files| files.clone()).unwrap_or_default();
+        LogStatus {
+            files: files.iter().map(|path| path.to_string_lossy().to_string()).collect(),
+            segment_index: self.progress.segment_index.load(Ordering::SeqCst) as u32,
+            paused: self.paused.load(Ordering::SeqCst),
+            bytes_logged: self.progress.bytes_logged.load(Ordering::SeqCst),
+            started_at: self.started_at,
+            error: self.progress.error.lock().ok().and_then(|error| error.clone()),
+        }
+    }
+
+    /// Write everything still queued, sync it to disk and close the files.
+    pub fn stop(mut self) {
+        self.finish();
+    }
+
+    fn note(&self, text: &str) {
+        if let Some(sender) = &self.sender {
+            let line = format!("{} {}\r\n", format_timestamp(&Local::now()), text);
+            let _ = sender.send(LogMessage::Note(line));
+        }
+    }
+
+    fn finish(&mut self) {
+        if self.sender.is_some() {
+            self.note("Log stopped");
+        }
+        // Closing the channel lets the writer drain it and exit
+        self.sender = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+impl Drop for SessionLog {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+struct SegmentFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    size: u64,
+}
+
+/// The open segment (one file per format) and the ones closed before it.
+/// All formats rotate together so a segment's files cover the same traffic.
+struct LogOutput {
+    settings: LogSettings,
+    port_label: String,
+    files: Vec<SegmentFile>,
+    segment_index: u32,
+    segment_started: Instant,
+    closed_segments: VecDeque<Vec<PathBuf>>,
+    progress: Arc<LogProgress>,
+}
+
+impl LogOutput {
+    fn open(settings: LogSettings, port_name: &str, progress: Arc<LogProgress>) -> Result<Self, String> {
+        let mut output = Self {
+            settings,
+            port_label: sanitize_file_name(port_name),
+            files: Vec::new(),
+            segment_index: 0,
+            segment_started: Instant::now(),
+            closed_segments: VecDeque::new(),
+            progress,
+        };
+        output.files = output.open_segment()?;
+        output.report_files();
+        Ok(output)
+    }
+
+    fn open_segment(&self) -> Result<Vec<SegmentFile>, String> {
+        let template = match &self.settings.name_template {
+            Some(template) => template.as_str(),
+            None if self.settings.rotates() => "{base}_{port}_{timestamp}_{format}",
+            None => "{base}_{format}",
+        };
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+
+        let mut files = Vec::new();
+        for format in &self.settings.formats {
+            let name = template
+                .replace("{base}", &self.settings.base_name)
+                .replace("{port}", &self.port_label)
+                .replace("{timestamp}", &timestamp)
+                .replace("{index}", &self.segment_index.to_string())
+                .replace("{format}", format.file_suffix());
+            let mut path = Path::new(&self.settings.directory).join(format!("{}.txt", name));
+            // Segments started within the same second would otherwise share a file
+            if self.settings.rotates() {
+                let mut n = 1;
+                while path.exists() || path.with_extension("txt.gz").exists() {
+                    path = Path::new(&self.settings.directory).join(format!("{}_{}.txt", name, n));
+                    n += 1;
+                }
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| format!("Failed to open log file {}: {}", path.display(), e))?;
+            let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+            files.push(SegmentFile { path, writer: BufWriter::new(file), size });
+        }
+        Ok(files)
+    }
+
+    /// Write `lines[i]` to the i-th file, rotating first if any file would
+    /// outgrow the size limit or the segment is past its time limit. Lines
+    /// are never split, so a frame always ends up whole in one segment.
+    fn write_lines(&mut self, lines: &[String]) -> Result<(), String> {
+        let over_size = self.settings.max_segment_bytes.is_some_and(|max| {
+            self.files
+                .iter()
+                .zip(lines)
+                .any(|(file, line)| file.size > 0 && file.size + line.len() as u64 > max)
+        });
+        if over_size || self.segment_expired() {
+            self.rotate();
+        }
+
+        for (file, line) in self.files.iter_mut().zip(lines) {
+            file.writer
+                .write_all(line.as_bytes())
+                .and_then(|_| file.writer.flush())
+                .map_err(|e| format!("Failed to write {}: {}", file.path.display(), e))?;
+            file.size += line.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn segment_expired(&self) -> bool {
+        self.settings
+            .max_segment_secs
+            .is_some_and(|secs| self.segment_started.elapsed() >= Duration::from_secs(secs))
+    }
+
+    /// Close the current segment and start the next. If the next one can't
+    /// be opened, keep writing to the current one rather than lose data.
+    fn rotate(&mut self) {
+        self.segment_index += 1;
+        let next = match self.open_segment() {
+            Ok(next) => next,
+            Err(e) => {
+                self.segment_index -= 1;
+                self.progress.set_error(format!("Failed to rotate log: {}", e));
+                return;
+            }
+        };
+        let closed = std::mem::replace(&mut self.files, next);
+        self.segment_started = Instant::now();
+        self.report_files();
+
+        let mut closed_paths = Vec::new();
+        for file in closed {
+            let path = file.path.clone();
+            if let Err(e) = close_segment_file(file) {
+                self.progress.set_error(e);
+            }
+            closed_paths.push(path);
+        }
+        if self.settings.gzip {
+            closed_paths = closed_paths
+                .into_iter()
+                .map(|path| match gzip_file(&path) {
+                    Ok(compressed) => compressed,
+                    Err(e) => {
+                        self.progress.set_error(e);
+                        path
+                    }
+                })
+                .collect();
+        }
+        self.closed_segments.push_back(closed_paths);
+        self.prune();
+    }
+
+    fn prune(&mut self) {
+        let Some(max) = self.settings.max_segments else {
+            return;
+        };
+        // The open segment counts too
+        while self.closed_segments.len() + 1 > max.max(1) {
+            let Some(oldest) = self.closed_segments.pop_front() else {
+                break;
+            };
+            for path in oldest {
+                if let Err(e) = fs::remove_file(&path) {
+                    self.progress.set_error(format!("Failed to delete {}: {}", path.display(), e));
+                }
+            }
+        }
+    }
+
+    fn sync(&mut self) -> Result<(), String> {
+        for file in &mut self.files {
+            file.writer
+                .flush()
+                .and_then(|_| file.writer.get_ref().sync_data())
+                .map_err(|e| format!("Failed to sync log: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn report_files(&self) {
+        if let Ok(mut files) = self.progress.files.lock() {
+            *files = self.files.iter().map(|file| file.path.clone()).collect();
+        }
+        self.progress.segment_index.store(self.segment_index as u64, Ordering::SeqCst);
+    }
+}
+
+fn close_segment_file(mut file: SegmentFile) -> Result<(), String> {
+    file.writer
+        .flush()
+        .and_then(|_| file.writer.get_ref().sync_all())
+        .map_err(|e| format!("Failed to close {}: {}", file.path.display(), e))
+}
+
+/// Compress `path` to `path.gz` and remove the original. The original is
+/// only removed once the compressed copy is safely on disk.
+fn gzip_file(path: &Path) -> Result<PathBuf, String> {
+    let mut gz_name = path.as_os_str().to_owned();
+    gz_name.push(".gz");
+    let gz_path = PathBuf::from(gz_name);
+
+    let compress = || -> std::io::Result<()> {
+        let mut input = File::open(path)?;
+        let output = File::create(&gz_path)?;
+        let mut encoder = GzEncoder::new(BufWriter::new(output), Compression::default());
+        std::io::copy(&mut input, &mut encoder)?;
+        encoder.finish()?.into_inner().map_err(|e| e.into_error())?.sync_all()
+    };
+    compress().map_err(|e| format!("Failed to compress {}: {}", path.display(), e))?;
+    fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    Ok(gz_path)
+}
+
+/// Port names like `/dev/ttyUSB0` or `TCP:host:port` as a file name part.
+fn sanitize_file_name(name: &str) -> String {
+    name.trim_start_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect()
+}
+
+fn run_writer(
+    output: &mut LogOutput,
+    receiver: mpsc::Receiver<LogMessage>,
+    sync_interval: Duration,
+    progress: &LogProgress,
+) -> Result<(), String> {
+    let mut last_sync = Instant::now();
+    let mut unsynced = false;
+
+    loop {
+        let message = match receiver.recv_timeout(sync_interval) {
+            Ok(message) => Some(message),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        match message {
+            Some(LogMessage::Record(record, channel)) => {
+                let lines: Vec<String> = output
+                    .settings
+                    .formats
+                    .iter()
+                    .map(|&format| format_line(format, &record, channel))
+                    .collect();
+                output.write_lines(&lines)?;
+                progress.bytes_logged.fetch_add(record.data.len() as u64, Ordering::SeqCst);
+                unsynced = true;
+            }
+            Some(LogMessage::Note(line)) => {
+                output.write_lines(&vec![line; output.files.len()])?;
+                unsynced = true;
+            }
+            // Close a segment on time even while the line is quiet
+            None => {
+                if output.segment_expired() && output.files.iter().any(|file| file.size > 0) {
+                    output.rotate();
+                }
+            }
+        }
+
+        if unsynced && last_sync.elapsed() >= sync_interval {
+            output.sync()?;
+            last_sync = Instant::now();
+            unsynced = false;
+        }
+    }
+
+    output.sync()
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Log everything the session sends and receives from now on.
+#[tauri::command]
+pub fn start_log(
+    state: State<'_, SerialState>,
+    session_id: String,
+    settings: LogSettings,
+) -> Result<LogStatus, String> {
+    let session = state.sessions.get(&session_id)?;
+    let log = SessionLog::start(&settings, &session_id, &session.port_name)?;
+    let status = log.status();
+    session.set_log(Some(log));
+    Ok(status)
+}
+
+/// Stop logging. Returns once the files are synced to disk.
+#[tauri::command]
+pub fn stop_log(state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    state.sessions.get(&session_id)?.set_log(None);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn pause_log(state: State<'_, SerialState>, session_id: String, paused: bool) -> Result<(), String> {
+    let session = state.sessions.get(&session_id)?;
+    let log = session.lock_log()?;
+    let log = log
+        .as_ref()
+        .ok_or_else(|| format!("Session '{}' is not logging", session_id))?;
+    log.set_paused(paused);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_log_status(state: State<'_, SerialState>, session_id: String) -> Result<Option<LogStatus>, String> {
+    let session = state.sessions.get(&session_id)?;
+    let log = session.lock_log()?;
+    Ok(log.as_ref().map(SessionLog::status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::record::Timestamp;
+
+    #[test]
+    fn test_log_formats() {
+        let now = Timestamp::now();
+        let record = Record {
+            session_id: "main".to_string(),
+            seq: 0,
+            direction: Direction::Tx,
+            first_byte: now,
+            last_byte: now,
+            data: b"AT\r\n".to_vec(),
+            marker: None,
+        };
+        let line = format_line(LogFormat::Ascii, &record, 0);
+        assert!(line.ends_with(" [TX] - AT<CR><LF>\r\n"));
+        assert!(format_line(LogFormat::Hex, &record, 2).ends_with(" [TX2] - 41 54 0D 0A\r\n"));
+        assert_eq!(format_data(LogFormat::Decimal, &[7, 65]), "007 065");
+        assert_eq!(format_data(LogFormat::Binary, &[0x81]), "10000001");
+
+        // Every format reads back to the same frame
+        for format in [LogFormat::Ascii, LogFormat::Hex, LogFormat::Decimal, LogFormat::Binary] {
+            let frame = parse_line(format, &format_line(format, &record, 1)).unwrap();
+            assert_eq!((frame.direction, frame.channel), (Direction::Tx, 1));
+            assert_eq!(frame.data, b"AT\r\n");
+        }
+        assert_eq!(parse_ascii("<1<CR>\u{e9}"), vec![b'<', b'1', 0x0D, 0xE9]);
+        assert_eq!(parse_line(LogFormat::Hex, "18/10/2026 14:23:01.123 Log stopped"), None);
+        assert_eq!(LogFormat::from_path(Path::new("/tmp/soak_COM1_3_dec.txt.gz")), Some(LogFormat::Decimal));
+    }
+
+    fn rx(data: &[u8]) -> Record {
+        let now = Timestamp::now();
+        Record {
+            session_id: "main".to_string(),
+            seq: 0,
+            direction: Direction::Rx,
+            first_byte: now,
+            last_byte: now,
+            data: data.to_vec(),
+            marker: None,
+        }
+    }
+
+    fn settings(dir: &Path, json: &str) -> LogSettings {
+        let mut settings: LogSettings = serde_json::from_str(json).unwrap();
+        settings.directory = dir.to_string_lossy().to_string();
+        settings
+    }
+
+    #[test]
+    fn test_session_log_writes_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = settings(dir.path(), r#"{"directory":"","base_name":"test","formats":["ascii","hex"]}"#);
+        let log = SessionLog::start(&settings, "main", "COM1").unwrap();
+        log.write(&rx(b"OK"), 0);
+        log.stop();
+
+        let ascii = fs::read_to_string(dir.path().join("test_asc.txt")).unwrap();
+        assert!(ascii.contains("[RX] - OK\r\n"));
+        assert!(ascii.contains("Log stopped"));
+        let hex = fs::read_to_string(dir.path().join("test_hex.txt")).unwrap();
+        assert!(hex.contains("[RX] - 4F 4B\r\n"));
+    }
+
+    #[test]
+    fn test_session_log_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = settings(
+            dir.path(),
+            r#"{"directory":"","formats":["hex"],"max_segment_bytes":100,"max_segments":2,"gzip":true,
+                "name_template":"soak_{port}_{index}_{format}"}"#,
+        );
+        let log = SessionLog::start(&settings, "main", "/dev/ttyUSB0").unwrap();
+        for _ in 0..10 {
+            log.write(&rx(&[0xAA; 8]), 0);
+        }
+        log.stop();
+
+        let mut names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        // Two lines never fit in 100 bytes except the last record and the
+        // short stop note, so the start note and ten records make segments
+        // 0 to 10. Older ones were pruned, the previous one compressed.
+        assert_eq!(names, vec!["soak_dev_ttyUSB0_10_hex.txt", "soak_dev_ttyUSB0_9_hex.txt.gz"]);
+        // Every line stayed whole and within the size limit
+        let last = fs::read_to_string(dir.path().join(&names[0])).unwrap();
+        assert!(last.len() <= 100);
+        assert!(last.ends_with("\r\n"));
+    }
+}
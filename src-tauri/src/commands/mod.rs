@@ -32,6 +32,8 @@
 
 pub mod serial;
 pub mod file;
+pub mod log;
+pub mod pcap;
 pub mod system;
 
 
@@ -0,0 +1,500 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file pcap.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use crate::serial::history::HistoryQuery;
+use crate::serial::record::{Direction, Record};
+use crate::serial::state::SerialState;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::Mutex;
+use std::sync::Arc;
+use tauri::State;
+
+// ============================================================================
+// pcapng Writer
+// ============================================================================
+
+/// DLT_USER0: raw serial bytes with no link-layer header. Wireshark can be
+/// told how to dissect it under Preferences > Protocols > DLT_USER.
+const LINKTYPE_USER0: u16 = 147;
+
+const BLOCK_SECTION_HEADER: u32 = 0x0A0D_0D0A;
+const BLOCK_INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+const BLOCK_ENHANCED_PACKET: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+const OPT_END: u16 = 0;
+const OPT_SHB_USERAPPL: u16 = 4;
+const OPT_IF_NAME: u16 = 2;
+const OPT_IF_DESCRIPTION: u16 = 3;
+const OPT_IF_TSRESOL: u16 = 9;
+const OPT_COMMENT: u16 = 1;
+const OPT_EPB_FLAGS: u16 = 2;
+
+/// epb_flags direction bits
+const FLAG_INBOUND: u32 = 0b01;
+const FLAG_OUTBOUND: u32 = 0b10;
+
+/// Writes records as a pcapng stream: one interface per session channel,
+/// microsecond wall-clock timestamps (the default resolution) and the
+/// direction in each packet's flags. Timestamps are from the first byte of
+/// the frame.
+pub struct PcapngWriter<W: Write> {
+    out: W,
+    interfaces: HashMap<(String, u8), u32>,
+}
+
+impl<W: Write> PcapngWriter<W> {
+    pub fn new(mut out: W) -> std::io::Result<Self> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+        body.extend_from_slice(&1u16.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        // Section length not known up front
+        body.extend_from_slice(&(-1i64).to_le_bytes());
+        push_option(&mut body, OPT_SHB_USERAPPL, b"termLight");
+        push_option(&mut body, OPT_END, &[]);
+        write_block(&mut out, BLOCK_SECTION_HEADER, &body)?;
+        Ok(Self { out, interfaces: HashMap::new() })
+    }
+
+    pub fn write_record(&mut self, record: &Record, channel: u8, port_name: &str) -> std::io::Result<()> {
+        let interface = self.interface(&record.session_id, channel, port_name)?;
+        let micros = record.first_byte.wall_clock.timestamp_micros() as u64;
+        let flags = match record.direction {
+            Direction::Rx => FLAG_INBOUND,
+            Direction::Tx => FLAG_OUTBOUND,
+        };
+
+        let mut body = Vec::with_capacity(32 + record.data.len());
+        body.extend_from_slice(&interface.to_le_bytes());
+        body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(micros as u32).to_le_bytes());
+        body.extend_from_slice(&(record.data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(record.data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&record.data);
+        pad(&mut body);
+        push_option(&mut body, OPT_EPB_FLAGS, &flags.to_le_bytes());
+        if let Some(marker) = record.marker {
+            push_option(&mut body, OPT_COMMENT, marker.label().as_bytes());
+        }
+        push_option(&mut body, OPT_END, &[]);
+        write_block(&mut self.out, BLOCK_ENHANCED_PACKET, &body)
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+
+    /// Id of the interface for `session_id`/`channel`, describing it the
+    /// first time it is seen.
+    fn interface(&mut self, session_id: &str, channel: u8, port_name: &str) -> std::io::Result<u32> {
+        let key = (session_id.to_string(), channel);
+        if let Some(&id) = self.interfaces.get(&key) {
+            return Ok(id);
+        }
+
+        let name = if channel > 0 { format!("{}:{}", session_id, channel) } else { session_id.to_string() };
+        let mut body = Vec::new();
+        body.extend_from_slice(&LINKTYPE_USER0.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        // No snapshot limit
+        body.extend_from_slice(&0u32.to_le_bytes());
+        push_option(&mut body, OPT_IF_NAME, name.as_bytes());
+        push_option(&mut body, OPT_IF_DESCRIPTION, port_name.as_bytes());
+        push_option(&mut body, OPT_END, &[]);
+        write_block(&mut self.out, BLOCK_INTERFACE_DESCRIPTION, &body)?;
+
+        let id = self.interfaces.len() as u32;
+        self.interfaces.insert(key, id);
+        Ok(id)
+    }
+}
+
+fn pad(buf: &mut Vec<u8>) {
+    buf.resize(buf.len().next_multiple_of(4), 0);
+}
+
+fn push_option(buf: &mut Vec<u8>, code: u16, value: &[u8]) {
+    buf.extend_from_slice(&code.to_le_bytes());
+    buf.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    buf.extend_from_slice(value);
+    pad(buf);
+}
+
+fn write_block(out: &mut impl Write, block_type: u32, body: &[u8]) -> std::io::Result<()> {
+    let total = (12 + body.len()) as u32;
+    out.write_all(&block_type.to_le_bytes())?;
+    out.write_all(&total.to_le_bytes())?;
+    out.write_all(body)?;
+    out.write_all(&total.to_le_bytes())
+}
+
+// ============================================================================
+// pcapng Reader
+// ============================================================================
+
+/// A packet read back from a pcapng file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedPacket {
+    pub interface: u32,
+    /// Nanoseconds since the Unix epoch
+    pub timestamp_ns: u128,
+    /// From the packet's direction flags; `None` if it doesn't say
+    pub direction: Option<Direction>,
+    pub data: Vec<u8>,
+}
+
+/// Read the packets of a little-endian pcapng file (which is what
+/// `PcapngWriter` and Wireshark on x86 produce).
+pub fn read_pcapng(bytes: &[u8]) -> Result<Vec<CapturedPacket>, String> {
+    let u32_at = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let truncated = || "Capture file is truncated".to_string();
+
+    if u32_at(0) != Some(BLOCK_SECTION_HEADER) {
+        return Err("Not a pcapng file".to_string());
+    }
+    if u32_at(8) != Some(BYTE_ORDER_MAGIC) {
+        return Err("Only little-endian pcapng files are supported".to_string());
+    }
+
+    // Ticks per second of each interface's timestamps
+    let mut resolutions: Vec<u128> = Vec::new();
+    let mut packets = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let block_type = u32_at(offset).ok_or_else(truncated)?;
+        let len = u32_at(offset + 4).ok_or_else(truncated)? as usize;
+        if len < 12 || offset + len > bytes.len() {
+            return Err(truncated());
+        }
+        let end = offset + len - 4;
+
+        match block_type {
+            BLOCK_SECTION_HEADER => resolutions.clear(),
+            BLOCK_INTERFACE_DESCRIPTION => {
+                let mut resolution = 1_000_000;
+                for (code, value) in options(bytes, offset + 16, end) {
+                    if code == OPT_IF_TSRESOL && !value.is_empty() {
+                        let exponent = (value[0] & 0x7F) as u32;
+                        let base: u128 = if value[0] & 0x80 != 0 { 2 } else { 10 };
+                        resolution = base
+                            .checked_pow(exponent)
+                            .ok_or_else(|| format!("Unsupported timestamp resolution {}^-{}", base, exponent))?;
+                    }
+                }
+                resolutions.push(resolution);
+            }
+            BLOCK_ENHANCED_PACKET => {
+                // The fixed fields and the data must fit inside the block
+                let past_block = || "Packet data runs past the end of its block".to_string();
+                if len < 32 {
+                    return Err(past_block());
+                }
+                let interface = u32_at(offset + 8).ok_or_else(truncated)?;
+                let high = u32_at(offset + 12).ok_or_else(truncated)? as u128;
+                let low = u32_at(offset + 16).ok_or_else(truncated)? as u128;
+                let captured = u32_at(offset + 20).ok_or_else(truncated)? as usize;
+                if captured > end - (offset + 28) {
+                    return Err(past_block());
+                }
+                let data = &bytes[offset + 28..offset + 28 + captured];
+                let resolution = *resolutions
+                    .get(interface as usize)
+                    .ok_or_else(|| format!("Packet refers to unknown interface {}", interface))?;
+
+                let options_start = offset + 28 + captured.next_multiple_of(4);
+                let direction = options(bytes, options_start, end)
+                    .find(|(code, value)| *code == OPT_EPB_FLAGS && value.len() == 4)
+                    .and_then(|(_, value)| match u32::from_le_bytes([value[0], value[1], value[2], value[3]]) & 0b11 {
+                        FLAG_INBOUND => Some(Direction::Rx),
+                        FLAG_OUTBOUND => Some(Direction::Tx),
+                        _ => None,
+                    });
+
+                packets.push(CapturedPacket {
+                    interface,
+                    timestamp_ns: ((high << 32) | low) * 1_000_000_000 / resolution,
+                    direction,
+                    data: data.to_vec(),
+                });
+            }
+            _ => {}
+        }
+        offset += len;
+    }
+    Ok(packets)
+}
+
+/// The options between `start` and `end` as (code, value) pairs.
+fn options(bytes: &[u8], start: usize, end: usize) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut offset = start;
+    std::iter::from_fn(move || {
+        let header = bytes.get(offset..(offset + 4).min(end))?;
+        if header.len() < 4 {
+            return None;
+        }
+        let code = u16::from_le_bytes([header[0], header[1]]);
+        let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+        let value = bytes.get(offset + 4..(offset + 4 + len).min(end))?;
+        if code == OPT_END {
+            return None;
+        }
+        offset += 4 + len.next_multiple_of(4);
+        Some((code, value))
+    })
+}
+
+// ============================================================================
+// Live Capture
+// ============================================================================
+
+/// A pcapng file that sessions write to as traffic happens. Several
+/// sessions can share one capture; the file is closed when the last of them
+/// detaches.
+pub struct PcapCapture {
+    path: String,
+    writer: Mutex<PcapngWriter<BufWriter<File>>>,
+}
+
+impl PcapCapture {
+    pub fn create(path: &str) -> Result<Arc<Self>, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create capture file {}: {}", path, e))?;
+        let writer = PcapngWriter::new(BufWriter::new(file))
+            .map_err(|e| format!("Failed to write capture file {}: {}", path, e))?;
+        Ok(Arc::new(Self { path: path.to_string(), writer: Mutex::new(writer) }))
+    }
+
+    /// Append `record`, flushing so a crash loses nothing already captured.
+    pub fn write(&self, record: &Record, channel: u8, port_name: &str) {
+        let Ok(mut writer) = self.writer.lock() else {
+            return;
+        };
+        if let Err(e) = writer.write_record(record, channel, port_name).and_then(|_| writer.flush()) {
+            println!("Failed to write capture {}: {}", self.path, e);
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Write the history of `session_ids` to a pcapng file, merged in time
+/// order. Returns the number of packets written.
+#[tauri::command]
+pub fn export_pcapng(
+    state: State<'_, SerialState>,
+    session_ids: Vec<String>,
+    file_path: String,
+    query: Option<HistoryQuery>,
+) -> Result<usize, String> {
+    let query = query.unwrap_or_default();
+    let mut entries = Vec::new();
+    for session_id in &session_ids {
+        let session = state.sessions.get(session_id)?;
+        let history = session.lock_history()?;
+        entries.extend(
+            history
+                .entries(&query)
+                .into_iter()
+                .map(|(record, channel)| (record, channel, session.port_name.clone())),
+        );
+    }
+    entries.sort_by_key(|(record, _, _)| record.first_byte.monotonic_ns);
+
+    let file = File::create(&file_path).map_err(|e| format!("Failed to create {}: {}", file_path, e))?;
+    let write = || -> std::io::Result<()> {
+        let mut writer = PcapngWriter::new(BufWriter::new(file))?;
+        for (record, channel, port_name) in &entries {
+            writer.write_record(record, *channel, port_name)?;
+        }
+        writer.flush()
+    };
+    write().map_err(|e| format!("Failed to write {}: {}", file_path, e))?;
+    Ok(entries.len())
+}
+
+/// Capture everything `session_ids` send and receive to a new pcapng file
+/// from now on.
+#[tauri::command]
+pub fn start_capture(
+    state: State<'_, SerialState>,
+    session_ids: Vec<String>,
+    file_path: String,
+) -> Result<(), String> {
+    let sessions = session_ids
+        .iter()
+        .map(|session_id| state.sessions.get(session_id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let capture = PcapCapture::create(&file_path)?;
+    for session in sessions {
+        session.set_capture(Some(capture.clone()));
+    }
+    println!("Capturing {:?} to {}", session_ids, file_path);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_capture(state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    state.sessions.get(&session_id)?.set_capture(None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::record::Timestamp;
+
+    fn u32_at(buf: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_pcapng_blocks() {
+        let now = Timestamp::now();
+        let record = |direction, data: &[u8]| Record {
+            session_id: "main".to_string(),
+            seq: 0,
+            direction,
+            first_byte: now,
+            last_byte: now,
+            data: data.to_vec(),
+            marker: None,
+        };
+
+        let mut writer = PcapngWriter::new(Vec::new()).unwrap();
+        writer.write_record(&record(Direction::Tx, b"AT\r"), 0, "COM1").unwrap();
+        writer.write_record(&record(Direction::Rx, b"OK"), 0, "COM1").unwrap();
+        writer.write_record(&record(Direction::Rx, b"!"), 2, "COM2").unwrap();
+        let buf = writer.out;
+
+        // Walk the blocks: SHB, IDB, EPB, EPB, IDB, EPB
+        let mut types = Vec::new();
+        let mut offset = 0;
+        while offset < buf.len() {
+            let len = u32_at(&buf, offset + 4) as usize;
+            assert_eq!(len % 4, 0);
+            assert_eq!(u32_at(&buf, offset + len - 4) as usize, len);
+            types.push(u32_at(&buf, offset));
+            if u32_at(&buf, offset) == BLOCK_ENHANCED_PACKET {
+                let interface = u32_at(&buf, offset + 8);
+                let captured = u32_at(&buf, offset + 20) as usize;
+                let flags_at = offset + 28 + captured.next_multiple_of(4);
+                assert_eq!(u32_at(&buf, flags_at), OPT_EPB_FLAGS as u32 | (4 << 16));
+                let expected = if captured == 3 { FLAG_OUTBOUND } else { FLAG_INBOUND };
+                assert_eq!(u32_at(&buf, flags_at + 4), expected);
+                assert_eq!(interface, if captured == 1 { 1 } else { 0 });
+            }
+            offset += len;
+        }
+        assert_eq!(types, vec![
+            BLOCK_SECTION_HEADER,
+            BLOCK_INTERFACE_DESCRIPTION,
+            BLOCK_ENHANCED_PACKET,
+            BLOCK_ENHANCED_PACKET,
+            BLOCK_INTERFACE_DESCRIPTION,
+            BLOCK_ENHANCED_PACKET,
+        ]);
+    }
+
+    #[test]
+    fn test_read_pcapng_resolutions_and_bad_blocks() {
+        let interface = |tsresol: u8| {
+            let mut body = Vec::new();
+            body.extend_from_slice(&LINKTYPE_USER0.to_le_bytes());
+            body.extend_from_slice(&[0; 6]);
+            push_option(&mut body, OPT_IF_TSRESOL, &[tsresol]);
+            push_option(&mut body, OPT_END, &[]);
+            body
+        };
+        let packet = |interface: u32, ticks: u64, captured: u32, data: &[u8]| {
+            let mut body = Vec::new();
+            body.extend_from_slice(&interface.to_le_bytes());
+            body.extend_from_slice(&((ticks >> 32) as u32).to_le_bytes());
+            body.extend_from_slice(&(ticks as u32).to_le_bytes());
+            body.extend_from_slice(&captured.to_le_bytes());
+            body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            body.extend_from_slice(data);
+            pad(&mut body);
+            body
+        };
+        let capture = |blocks: &[(u32, Vec<u8>)]| {
+            let mut out = PcapngWriter::new(Vec::new()).unwrap().out;
+            for (block_type, body) in blocks {
+                write_block(&mut out, *block_type, body).unwrap();
+            }
+            out
+        };
+
+        // 2^-10 s and 10^-9 s ticks
+        let packets = read_pcapng(&capture(&[
+            (BLOCK_INTERFACE_DESCRIPTION, interface(0x80 | 10)),
+            (BLOCK_INTERFACE_DESCRIPTION, interface(9)),
+            (BLOCK_ENHANCED_PACKET, packet(0, 3 * 1024 + 512, 2, b"ab")),
+            (BLOCK_ENHANCED_PACKET, packet(1, 1_500_000_000, 1, b"c")),
+        ]))
+        .unwrap();
+        let read: Vec<(u128, &[u8])> = packets.iter().map(|p| (p.timestamp_ns, p.data.as_slice())).collect();
+        assert_eq!(read, vec![(3_500_000_000, &b"ab"[..]), (1_500_000_000, &b"c"[..])]);
+
+        // 10^-39 doesn't fit
+        let err = read_pcapng(&capture(&[(BLOCK_INTERFACE_DESCRIPTION, interface(39))])).unwrap_err();
+        assert!(err.contains("resolution"), "{}", err);
+
+        // A captured length past the block, even though the file goes on
+        let err = read_pcapng(&capture(&[
+            (BLOCK_INTERFACE_DESCRIPTION, interface(6)),
+            (BLOCK_ENHANCED_PACKET, packet(0, 0, 64, b"abcd")),
+            (BLOCK_ENHANCED_PACKET, packet(0, 0, 64, &[0; 64])),
+        ]))
+        .unwrap_err();
+        assert!(err.contains("past the end"), "{}", err);
+
+        // A block too short to hold a packet header
+        let err = read_pcapng(&capture(&[
+            (BLOCK_INTERFACE_DESCRIPTION, interface(6)),
+            (BLOCK_ENHANCED_PACKET, vec![0; 8]),
+        ]))
+        .unwrap_err();
+        assert!(err.contains("past the end"), "{}", err);
+    }
+}
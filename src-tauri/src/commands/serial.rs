@@ -29,114 +29,601 @@
  * @date 2025-11-19
  */
 
-use crate::serial::state::SerialState;
+use crate::commands::file::{ProjectData, ReceiveCommand};
+use crate::serial::bridge::BridgeSettings;
+use crate::serial::config::{LineCapabilities, SerialConfig};
+use crate::serial::framing::{Framer, FramingConfig};
+use crate::serial::history::{HistoryInfo, HistoryLimits, HistoryQuery, SearchMatch};
+use crate::serial::matcher::{ReceiveMatch, ReceiveMatcher};
+use crate::serial::modem::{read_lines, spawn_modem_poller, write_line, ModemLines, OutputLine};
+use crate::serial::port::{
+    Endpoint, PeerEvent, SerialTransport, TcpServerTransport, TcpTransport, Transport,
+    UdpTransport, MAX_DATAGRAM_SIZE, READ_TIMEOUT,
+};
+use crate::serial::reconnect::ReconnectPolicy;
+use crate::serial::record::{Direction, Marker, Record, Timestamp};
+use crate::serial::replay::{ReplayControl, ReplayMode, ReplayStatus, ReplayTransport};
+use crate::serial::sequence::{parse_sequence, SendStep};
+use crate::serial::simulator::{RuleStatus, Simulator};
+use crate::serial::state::{emit_connection_state, ConnectionState, SerialState, Session};
+use crate::serial::stream::{spawn_stream_sender, Push, RecordStream, DEFAULT_BUFFER_BYTES};
+use crate::serial::tap::TapSettings;
+use crate::serial::virtual_port::LoopbackTransport;
 use serde::{Deserialize, Serialize};
-use serialport::{DataBits, Parity, StopBits};
-use std::io::{Read, Write};
-use std::sync::atomic::Ordering;
+use serialport::{SerialPort, SerialPortType, UsbPortInfo};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter, State};
+use tauri::ipc::{Channel, InvokeResponseBody};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PortInfo {
     pub name: String,
     pub description: Option<String>,
+    /// "usb", "pci", "bluetooth" or "unknown"
+    pub port_type: String,
+    pub vid: Option<u16>,
+    pub pid: Option<u16>,
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    /// Stable `/dev/serial/by-id/...` link to this port (Linux only)
+    pub by_id_path: Option<String>,
 }
 
 #[derive(Clone, Serialize)]
-struct SerialPayload {
-    data: Vec<u8>,
+struct ClientPayload {
+    session_id: String,
+    address: String,
+}
+
+/// Payload of `tap-payload` and `bridge-payload` events.
+#[derive(Clone, Serialize)]
+struct ChannelPayload {
+    #[serde(flatten)]
+    record: Record,
+    /// 1 or 2: the side of the session the chunk was read from
+    channel: u8,
+    port_name: String,
+}
+
+#[derive(Clone, Serialize)]
+struct DisconnectedPayload {
+    session_id: String,
+    reason: String,
+    /// Whether the session will try to reopen the port
+    reconnecting: bool,
+}
+
+#[derive(Clone, Serialize)]
+struct ReconnectedPayload {
+    session_id: String,
+    /// May differ from the original path if the device re-enumerated
+    port_name: String,
+    attempts: u32,
+}
+
+#[derive(Clone, Serialize)]
+struct ReconnectFailedPayload {
+    session_id: String,
+    attempts: u32,
+}
+
+/// Payload of `receive-match`: a receive sequence found in received data.
+#[derive(Clone, Serialize)]
+struct ReceiveMatchPayload {
+    session_id: String,
+    #[serde(flatten)]
+    found: ReceiveMatch,
+    /// 0 for single-port sessions, otherwise the side it was received on
+    channel: u8,
+    /// Record the match ended in
+    seq: u64,
+    timestamp: Timestamp,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub port_name: String,
+}
+
+/// Snapshot returned by `get_connection_status`, for a window that missed
+/// the `connection-state` events.
+#[derive(Clone, Serialize)]
+pub struct ConnectionStatus {
+    pub session_id: String,
+    pub port_name: String,
+    #[serde(flatten)]
+    pub state: ConnectionState,
+    pub is_connected: bool,
+    /// Packet events that failed to reach the frontend
+    pub emit_failures: u64,
+    /// Bytes the attached stream had to drop because the frontend fell behind
+    pub dropped_bytes: u64,
+}
+
+/// Parity and stop bit settings this platform can open a port with.
+#[tauri::command]
+pub fn get_line_capabilities() -> LineCapabilities {
+    LineCapabilities::current()
 }
 
 #[tauri::command]
 pub fn list_ports() -> Result<Vec<PortInfo>, String> {
-    match serialport::available_ports() {
-        Ok(ports) => {
-            let port_list = ports
-                .into_iter()
-                .map(|p| PortInfo {
+    enumerate_ports()
+}
+
+/// Every serial port the OS reports, with USB identity where available.
+pub fn enumerate_ports() -> Result<Vec<PortInfo>, String> {
+    let ports = serialport::available_ports()
+        .map_err(|e| format!("Failed to list ports: {}", e))?;
+    let by_id = by_id_paths();
+
+    Ok(ports
+        .into_iter()
+        .map(|p| {
+            let by_id_path = by_id.get(&p.port_name).cloned();
+            match p.port_type {
+                SerialPortType::UsbPort(usb) => PortInfo {
+                    description: Some(usb_description(&usb)),
+                    port_type: "usb".to_string(),
+                    vid: Some(usb.vid),
+                    pid: Some(usb.pid),
+                    serial_number: usb.serial_number,
+                    manufacturer: usb.manufacturer,
+                    product: usb.product,
+                    name: p.port_name,
+                    by_id_path,
+                },
+                other => PortInfo {
                     name: p.port_name,
-                    description: None, // SerialPortInfo doesn't always provide a description in a standard way
-                })
-                .collect();
-            Ok(port_list)
+                    description: None,
+                    port_type: match other {
+                        SerialPortType::PciPort => "pci",
+                        SerialPortType::BluetoothPort => "bluetooth",
+                        _ => "unknown",
+                    }
+                    .to_string(),
+                    vid: None,
+                    pid: None,
+                    serial_number: None,
+                    manufacturer: None,
+                    product: None,
+                    by_id_path,
+                },
+            }
+        })
+        .collect())
+}
+
+/// Human-readable label such as "FTDI FT232R USB UART (A1B2C3)".
+fn usb_description(usb: &UsbPortInfo) -> String {
+    let mut label = match (&usb.manufacturer, &usb.product) {
+        (Some(m), Some(p)) if p.starts_with(m.as_str()) => p.clone(),
+        (Some(m), Some(p)) => format!("{} {}", m, p),
+        (Some(name), None) | (None, Some(name)) => name.clone(),
+        (None, None) => format!("USB {:04X}:{:04X}", usb.vid, usb.pid),
+    };
+    if let Some(serial) = &usb.serial_number {
+        label.push_str(&format!(" ({})", serial));
+    }
+    label
+}
+
+/// Map each device node to its persistent `/dev/serial/by-id` link, which
+/// stays the same across replugs while `/dev/ttyUSBn` numbering doesn't.
+#[cfg(target_os = "linux")]
+fn by_id_paths() -> HashMap<String, String> {
+    let mut paths = HashMap::new();
+    let Ok(entries) = std::fs::read_dir("/dev/serial/by-id") else {
+        return paths;
+    };
+    for entry in entries.flatten() {
+        let link = entry.path();
+        if let Ok(target) = std::fs::canonicalize(&link) {
+            paths.insert(
+                target.to_string_lossy().to_string(),
+                link.to_string_lossy().to_string(),
+            );
         }
-        Err(e) => Err(format!("Failed to list ports: {}", e)),
     }
+    paths
+}
+
+#[cfg(not(target_os = "linux"))]
+fn by_id_paths() -> HashMap<String, String> {
+    HashMap::new()
 }
 
 #[tauri::command]
-pub fn open_port(
-    app: AppHandle,
+pub fn open_port<R: Runtime>(
+    app: AppHandle<R>,
     state: State<'_, SerialState>,
-    port_name: String,
-    baud_rate: u32,
-    data_bits: u8,
-    stop_bits: f32,
-    parity: String,
+    session_id: String,
+    config: SerialConfig,
+    reconnect: Option<ReconnectPolicy>,
+    framing: Option<FramingConfig>,
 ) -> Result<(), String> {
-    let mut port_lock = state.port.lock().map_err(|_| "Failed to lock port mutex")?;
+    ensure_session_free(&state, &session_id)?;
+    let framer = framing.unwrap_or_default().build()?;
+
+    let port_name = config.port_name.clone();
+    emit_connection_state(&app, &session_id, &port_name, ConnectionState::Opening);
+    let port = match open_transport(&config) {
+        Ok(port) => port,
+        Err(e) => {
+            emit_connection_state(&app, &session_id, &port_name, ConnectionState::Error {
+                message: e.clone(),
+                kind: "OpenFailed".to_string(),
+            });
+            emit_connection_state(&app, &session_id, &port_name, ConnectionState::Closed);
+            return Err(e);
+        }
+    };
+
+    start_session(app, &state, &session_id, port, &port_name, reconnect.map(|policy| (config, policy)), framer)
+}
 
-    if port_lock.is_some() {
-        return Err("Port is already open".to_string());
+/// Open whatever `config.port_name` points to. The line settings only apply
+/// when it turns out to be a serial port.
+fn open_transport(config: &SerialConfig) -> Result<Box<dyn Transport>, String> {
+    Ok(match Endpoint::parse(&config.port_name)? {
+        Endpoint::Serial(name) => Box::new(SerialTransport::open(&SerialConfig {
+            port_name: name,
+            ..config.clone()
+        })?),
+        Endpoint::Tcp { host, port } => Box::new(TcpTransport::connect(&host, port)?),
+        Endpoint::Udp { host, port, local_port } => {
+            Box::new(UdpTransport::open(&host, port, local_port)?)
+        }
+        Endpoint::TcpServer { port } => Box::new(TcpServerTransport::bind(port)?),
+        Endpoint::Replay(path) => Box::new(ReplayTransport::open(Path::new(&path))?),
+        Endpoint::Loopback => Box::new(LoopbackTransport::default()),
+    })
+}
+
+/// The device paths behind `port_names`, as the port watcher will see them
+/// disappear: each serial path as given plus, for links such as
+/// `/dev/serial/by-id/...`, the node it resolves to. Network and virtual
+/// endpoints hold no device.
+fn device_paths(port_names: &[&str]) -> Vec<String> {
+    let mut devices = Vec::new();
+    for port_name in port_names {
+        if let Ok(Endpoint::Serial(path)) = Endpoint::parse(port_name) {
+            if let Ok(resolved) = std::fs::canonicalize(&path) {
+                let resolved = resolved.to_string_lossy().into_owned();
+                if resolved != path {
+                    devices.push(resolved);
+                }
+            }
+            devices.push(path);
+        }
     }
+    devices
+}
 
-    let d_bits = match data_bits {
-        5 => DataBits::Five,
-        6 => DataBits::Six,
-        7 => DataBits::Seven,
-        8 => DataBits::Eight,
-        _ => return Err("Invalid data bits".to_string()),
-    };
+/// Listen on a local TCP port and use the connecting client as the channel.
+/// `client-connected` / `client-disconnected` events report who is attached;
+/// a dropped client frees the slot for the next one without closing.
+#[tauri::command]
+pub fn listen_port<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialState>,
+    session_id: String,
+    local_port: u16,
+    framing: Option<FramingConfig>,
+) -> Result<(), String> {
+    ensure_session_free(&state, &session_id)?;
+    let framer = framing.unwrap_or_default().build()?;
 
-    let s_bits = if stop_bits == 1.0 {
-        StopBits::One
-    } else if stop_bits == 2.0 {
-        StopBits::Two
-    } else {
-        return Err("Invalid stop bits (only 1 and 2 supported)".to_string())
+    let port_name = format!("SERVER:{}", local_port);
+    emit_connection_state(&app, &session_id, &port_name, ConnectionState::Opening);
+    let port = match TcpServerTransport::bind(local_port) {
+        Ok(port) => Box::new(port),
+        Err(e) => {
+            emit_connection_state(&app, &session_id, &port_name, ConnectionState::Error {
+                message: e.clone(),
+                kind: "OpenFailed".to_string(),
+            });
+            emit_connection_state(&app, &session_id, &port_name, ConnectionState::Closed);
+            return Err(e);
+        }
     };
+    start_session(app, &state, &session_id, port, &port_name, None, framer)
+}
 
-    let p_parity = match parity.as_str() {
-        "None" => Parity::None,
-        "Even" => Parity::Even,
-        "Odd" => Parity::Odd,
-        _ => return Err("Invalid parity".to_string()),
-    };
+/// Create a pseudo-terminal pair and open its master side as a session.
+/// Returns the slave path (`/dev/pts/N`) for another program to open as if
+/// it were the device.
+#[tauri::command]
+pub fn open_virtual_pair<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialState>,
+    session_id: String,
+    framing: Option<FramingConfig>,
+) -> Result<String, String> {
+    ensure_session_free(&state, &session_id)?;
+    let framer = framing.unwrap_or_default().build()?;
 
-    let port = serialport::new(&port_name, baud_rate)
-        .data_bits(d_bits)
-        .stop_bits(s_bits)
-        .parity(p_parity)
-        .timeout(Duration::from_millis(100))
-        .open()
-        .map_err(|e| format!("Failed to open port: {}", e))?;
+    #[cfg(target_os = "linux")]
+    {
+        let port = Box::new(crate::serial::virtual_port::PtyTransport::open()?);
+        let path = port.path().to_string();
+        start_session(app, &state, &session_id, port, &path, None, framer)?;
+        Ok(path)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (app, framer);
+        Err("Virtual port pairs are only available on Linux".to_string())
+    }
+}
 
-    // Clear any stale data in the receive buffer
-    let _ = port.clear(serialport::ClearBuffer::Input);
-    
-    // Give the device a moment to settle after port open
-    std::thread::sleep(Duration::from_millis(50));
-    
-    // Clear buffer again to discard any bytes sent during port initialization
-    let _ = port.clear(serialport::ClearBuffer::Input);
+fn ensure_session_free(state: &SerialState, session_id: &str) -> Result<(), String> {
+    if session_id.is_empty() {
+        return Err("Session id must not be empty".to_string());
+    }
+    if state.sessions.contains(session_id)? {
+        return Err(format!("Session '{}' is already open", session_id));
+    }
+    Ok(())
+}
+
+/// Register `port` as session `session_id` and start its read thread.
+/// With `reconnect` set, the session reopens the same port after a drop.
+fn start_session<R: Runtime>(
+    app: AppHandle<R>,
+    state: &SerialState,
+    session_id: &str,
+    port: Box<dyn Transport>,
+    port_name: &str,
+    reconnect: Option<(SerialConfig, ReconnectPolicy)>,
+    framer: Box<dyn Framer>,
+) -> Result<(), String> {
+    let WorkerHandles { read_port, poll_port } = worker_handles(port.as_ref())?;
     
-    // Clone port for the read thread
-    let mut read_port = port.try_clone().map_err(|e| format!("Failed to clone port: {}", e))?;
+    let session = state.sessions.insert(session_id, Session::new(session_id, port_name, Some(port)))?;
+    session.set_devices(device_paths(&[port_name]));
+    let should_stop = session.should_stop.clone();
     
-    *port_lock = Some(port);
-    state.is_connected.store(true, Ordering::SeqCst);
-    state.should_stop.store(false, Ordering::SeqCst);
+    println!("Port {} opened as session '{}'. Starting read thread...", port_name, session_id);
+    session.set_connection_state(&app, ConnectionState::Open);
+
+    if let Some(poll_port) = poll_port {
+        spawn_modem_poller(app.clone(), session_id.to_string(), poll_port, should_stop.clone());
+    }
+
+    let reconnect = reconnect.map(|(config, policy)| Reconnect {
+        usb_serial_number: usb_serial_number(&config.port_name),
+        config,
+        policy,
+    });
+
+    let ctx = ReadContext {
+        app,
+        session_id: session_id.to_string(),
+        session,
+        channel: None,
+        forward_to: None,
+        reconnect,
+    };
+    spawn_read_thread(ctx, read_port, framer, should_stop);
+
+    Ok(())
+}
+
+/// Handles the background threads of a session get on its port.
+struct WorkerHandles {
+    read_port: Box<dyn Transport>,
+    /// Only serial ports have modem lines to poll
+    poll_port: Option<Box<dyn Transport>>,
+}
 
-    let should_stop = state.should_stop.clone();
+/// Prepare a freshly opened port for the background threads.
+fn worker_handles(port: &dyn Transport) -> Result<WorkerHandles, String> {
+    // Discard any bytes sent during port initialization
+    let _ = port.clear_input();
     
-    println!("Port {} opened successfully. Starting read thread...", port_name);
+    let read_port = port.try_clone_transport().map_err(|e| format!("Failed to clone port: {}", e))?;
+    let mut poll_port = port.try_clone_transport().map_err(|e| format!("Failed to clone port: {}", e))?;
+    let poll_port = poll_port.serial_port().is_some().then_some(poll_port);
+    Ok(WorkerHandles { read_port, poll_port })
+}
+
+/// USB serial number of `port_name`, used to find the device again if it
+/// comes back under a different path.
+fn usb_serial_number(port_name: &str) -> Option<String> {
+    enumerate_ports()
+        .ok()?
+        .into_iter()
+        .find(|p| p.name == port_name)
+        .and_then(|p| p.serial_number)
+}
+
+/// What a read thread needs to bring its session back after the port drops.
+struct Reconnect {
+    config: SerialConfig,
+    policy: ReconnectPolicy,
+    usb_serial_number: Option<String>,
+}
+
+impl Reconnect {
+    /// Where the device is now: the port with the same USB serial number if
+    /// it re-enumerated under a new name, otherwise the original path.
+    fn locate_port(&self) -> String {
+        self.usb_serial_number
+            .as_ref()
+            .and_then(|serial| {
+                enumerate_ports()
+                    .ok()?
+                    .into_iter()
+                    .find(|p| p.serial_number.as_ref() == Some(serial))
+            })
+            .map(|p| p.name)
+            .unwrap_or_else(|| self.config.port_name.clone())
+    }
+}
+
+/// Labels packets from one side of a two-port session (monitoring or bridge).
+struct ChannelLabel {
+    event: &'static str,
+    index: u8,
+    port_name: String,
+}
 
+/// Who a read thread reports to and how its packets are labelled.
+struct ReadContext<R: Runtime> {
+    app: AppHandle<R>,
+    session_id: String,
+    session: Arc<Session>,
+    /// Set for two-port sessions; packets go out as `channel.event` instead of `serial-payload`
+    channel: Option<ChannelLabel>,
+    /// Bridge sessions write everything read here straight to the other side
+    forward_to: Option<Box<dyn Transport>>,
+    /// Set when the session should reopen its port after a drop
+    reconnect: Option<Reconnect>,
+}
+
+impl<R: Runtime> ReadContext<R> {
+    fn emit_packet(&self, data: Vec<u8>, first_byte: Timestamp, last_byte: Timestamp) {
+        println!("Emitting packet with {} bytes: {:?}", data.len(), data);
+        let record = Record {
+            session_id: self.session_id.clone(),
+            seq: self.session.next_seq(),
+            direction: Direction::Rx,
+            first_byte,
+            last_byte,
+            data,
+            marker: None,
+        };
+        deliver(&self.app, &self.session, record, self.channel.as_ref());
+    }
+
+    fn forward(&mut self, data: &[u8]) {
+        if let Some(target) = self.forward_to.as_mut() {
+            if let Err(e) = target.write_all(data).and_then(|_| target.flush()) {
+                println!("Failed to forward {} bytes: {}", data.len(), e);
+            }
+        }
+    }
+
+    fn emit_disconnected(&self, reason: &str) {
+        let payload = DisconnectedPayload {
+            session_id: self.session_id.clone(),
+            reason: reason.to_string(),
+            reconnecting: self.reconnect.is_some(),
+        };
+        if let Err(e) = self.app.emit("disconnected", payload) {
+            println!("Failed to emit disconnected event: {}", e);
+        }
+    }
+
+    /// Retry opening the session's port with backoff until it succeeds, the
+    /// policy gives up or the session is closed. On success the session's
+    /// write handle is swapped for the new port and the new read handle is
+    /// returned.
+    fn reconnect(&self, should_stop: &AtomicBool) -> Option<Box<dyn Transport>> {
+        let reconnect = self.reconnect.as_ref()?;
+        self.session.set_connection_state(&self.app, ConnectionState::Reconnecting);
+
+        let mut attempt = 0;
+        loop {
+            let Some(delay) = reconnect.policy.delay(attempt) else {
+                println!("Giving up reconnecting session '{}' after {} attempts", self.session_id, attempt);
+                let payload = ReconnectFailedPayload { session_id: self.session_id.clone(), attempts: attempt };
+                if let Err(e) = self.app.emit("reconnect-failed", payload) {
+                    println!("Failed to emit reconnect-failed event: {}", e);
+                }
+                return None;
+            };
+            if !sleep_unless_stopped(delay, should_stop) {
+                return None;
+            }
+            attempt += 1;
+
+            let port_name = reconnect.locate_port();
+            let config = SerialConfig { port_name: port_name.clone(), ..reconnect.config.clone() };
+            let (port, WorkerHandles { read_port, poll_port }) = match open_transport(&config)
+                .and_then(|port| worker_handles(port.as_ref()).map(|handles| (port, handles)))
+            {
+                Ok(opened) => opened,
+                Err(e) => {
+                    println!("Reconnect attempt {} on {} failed: {}", attempt, port_name, e);
+                    continue;
+                }
+            };
+
+            // The session may have been closed while the port was opening
+            if should_stop.load(Ordering::SeqCst) {
+                return None;
+            }
+
+            match self.session.port.lock() {
+                Ok(mut port_lock) => *port_lock = Some(port),
+                Err(_) => return None,
+            }
+            self.session.set_devices(device_paths(&[&port_name]));
+            self.session.set_connection_state(&self.app, ConnectionState::Open);
+
+            if let Some(poll_port) = poll_port {
+                spawn_modem_poller(self.app.clone(), self.session_id.clone(), poll_port, self.session.should_stop.clone());
+            }
+
+            println!("Session '{}' reconnected on {} after {} attempts", self.session_id, port_name, attempt);
+            let payload = ReconnectedPayload { session_id: self.session_id.clone(), port_name, attempts: attempt };
+            if let Err(e) = self.app.emit("reconnected", payload) {
+                println!("Failed to emit reconnected event: {}", e);
+            }
+            return Some(read_port);
+        }
+    }
+
+    /// The read thread is gone for good: unless the session was closed on
+    /// purpose, mark it closed and drop it so its id can be reused.
+    fn finish(&self, should_stop: &AtomicBool) {
+        if should_stop.load(Ordering::SeqCst) {
+            return;
+        }
+        self.session.set_connection_state(&self.app, ConnectionState::Closed);
+        let app_state = self.app.state::<SerialState>();
+        if let Err(e) = app_state.sessions.close_if_current(&self.session_id, &self.session) {
+            println!("Failed to remove session '{}': {}", self.session_id, e);
+        }
+    }
+
+    fn emit_peer_event(&self, event: PeerEvent) {
+        let (name, addr) = match event {
+            PeerEvent::Connected(addr) => ("client-connected", addr),
+            PeerEvent::Disconnected(addr) => ("client-disconnected", addr),
+        };
+        println!("{}: {}", name, addr);
+        if let Err(e) = self.app.emit(name, ClientPayload { session_id: self.session_id.clone(), address: addr.to_string() }) {
+            println!("Failed to emit {} event: {}", name, e);
+        }
+    }
+}
+
+/// Read from `read_port` until `should_stop` is set or the channel fails,
+/// cutting the stream into frames with `framer` and emitting one packet
+/// event per frame. Datagram transports skip framing and emit one event per
+/// datagram.
+fn spawn_read_thread<R: Runtime>(
+    mut ctx: ReadContext<R>,
+    mut read_port: Box<dyn Transport>,
+    mut framer: Box<dyn Framer>,
+    should_stop: Arc<AtomicBool>,
+) {
     std::thread::spawn(move || {
-        let mut serial_buf: Vec<u8> = vec![0; 1024];
-        let mut packet_buffer: Vec<u8> = Vec::new();
-        let mut last_receive_time = std::time::Instant::now();
-        let packet_timeout = Duration::from_millis(5); // Group data within 5ms as one packet
+        let is_datagram = read_port.is_datagram();
+        let mut serial_buf: Vec<u8> = vec![0; if is_datagram { MAX_DATAGRAM_SIZE } else { 1024 }];
+        // Arrival of the oldest byte still held by the framer, and of the newest
+        let mut first_receive_time = Timestamp::now();
+        let mut last_receive_time = first_receive_time;
+        let mut read_timeout = READ_TIMEOUT;
         
         loop {
             if should_stop.load(Ordering::SeqCst) {
@@ -144,68 +631,886 @@ pub fn open_port(
                 break;
             }
 
+            // Block in read until data arrives or a pending frame's idle gap
+            // runs out instead of polling
+            let wanted_timeout = match framer.idle_timeout() {
+                Some(gap) if framer.has_pending() => gap
+                    .saturating_sub(last_receive_time.elapsed())
+                    .clamp(Duration::from_millis(1), READ_TIMEOUT),
+                _ => READ_TIMEOUT,
+            };
+            if wanted_timeout != read_timeout {
+                if let Err(e) = read_port.set_read_timeout(wanted_timeout) {
+                    println!("Failed to set read timeout: {}", e);
+                }
+                read_timeout = wanted_timeout;
+            }
+
             match read_port.read(&mut serial_buf) {
+                Ok(t) if is_datagram && t > 0 => {
+                    println!("Received datagram with {} bytes", t);
+                    ctx.forward(&serial_buf[..t]);
+                    let now = Timestamp::now();
+                    ctx.emit_packet(serial_buf[..t].to_vec(), now, now);
+                    continue;
+                }
                 Ok(t) if t > 0 => {
-                    // Append received data to packet buffer
-                    packet_buffer.extend_from_slice(&serial_buf[..t]);
-                    last_receive_time = std::time::Instant::now();
-                    println!("Received {} bytes (buffer now: {} bytes)", t, packet_buffer.len());
+                    let now = Timestamp::now();
+                    if !framer.has_pending() {
+                        first_receive_time = now;
+                    }
+                    last_receive_time = now;
+                    // Forward right away; only the UI copy waits for the frame to complete
+                    ctx.forward(&serial_buf[..t]);
+                    for frame in framer.push(&serial_buf[..t]) {
+                        ctx.emit_packet(frame, first_receive_time, now);
+                        first_receive_time = now;
+                    }
                 }
                 Ok(_) => {}
                 Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {}
                 Err(e) => {
-                    println!("Read error (stopping thread): {}", e);
-                    break;
+                    println!("Read error: {}", e);
+                    if let Some(frame) = framer.flush() {
+                        ctx.emit_packet(frame, first_receive_time, last_receive_time);
+                    }
+                    ctx.session.set_connection_state(&ctx.app, ConnectionState::Error {
+                        message: e.to_string(),
+                        kind: format!("{:?}", e.kind()),
+                    });
+                    ctx.emit_disconnected(&e.to_string());
+                    match ctx.reconnect(&should_stop) {
+                        Some(port) => {
+                            read_port = port;
+                            read_timeout = READ_TIMEOUT;
+                            continue;
+                        }
+                        None => {
+                            println!("Stopping read thread");
+                            break;
+                        }
+                    }
                 }
             }
+
+            while let Some(event) = read_port.take_peer_event() {
+                ctx.emit_peer_event(event);
+            }
             
-            // If we have data and enough time has passed without new data, emit the packet
-            if !packet_buffer.is_empty() && last_receive_time.elapsed() >= packet_timeout {
-                println!("Emitting packet with {} bytes: {:?}", packet_buffer.len(), packet_buffer);
-                if let Err(e) = app.emit("serial-payload", SerialPayload { data: packet_buffer.clone() }) {
-                    println!("Failed to emit serial-payload event: {}", e);
+            // If the framer goes by idle time and the line has been quiet long enough, emit what it has
+            if let Some(timeout) = framer.idle_timeout() {
+                if framer.has_pending() && last_receive_time.elapsed() >= timeout {
+                    if let Some(frame) = framer.flush() {
+                        ctx.emit_packet(frame, first_receive_time, last_receive_time);
+                    }
                 }
-                packet_buffer.clear();
             }
-            
-            // Small sleep to prevent 100% CPU usage
-            std::thread::sleep(Duration::from_millis(1));
         }
+
+        ctx.finish(&should_stop);
     });
+}
+
+/// Sleep for `duration` in short slices, returning early (with `false`) if
+/// `should_stop` gets set.
+fn sleep_unless_stopped(duration: Duration, should_stop: &AtomicBool) -> bool {
+    let deadline = std::time::Instant::now() + duration;
+    while std::time::Instant::now() < deadline {
+        if should_stop.load(Ordering::SeqCst) {
+            return false;
+        }
+        std::thread::sleep(READ_TIMEOUT.min(deadline - std::time::Instant::now()));
+    }
+    !should_stop.load(Ordering::SeqCst)
+}
+
+#[tauri::command]
+pub fn close_port<R: Runtime>(app: AppHandle<R>, state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    let session = state.sessions.get(&session_id).ok();
+    // Dropping the session's handles closes the port
+    if state.sessions.close(&session_id)? {
+        println!("Session '{}' closed", session_id);
+    }
+    if let Some(session) = session {
+        session.set_connection_state(&app, ConnectionState::Closed);
+    }
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn close_port(state: State<'_, SerialState>) -> Result<(), String> {
-    let mut port_lock = state.port.lock().map_err(|_| "Failed to lock port mutex")?;
-    
-    if port_lock.is_none() {
-        return Ok(());
+pub fn send_data<R: Runtime>(app: AppHandle<R>, state: State<'_, SerialState>, session_id: String, data: Vec<u8>) -> Result<(), String> {
+    let session = state.sessions.get(&session_id)?;
+    write_and_record(&app, &session, data)
+}
+
+/// Write `data` to the session and emit it as a TX record, numbered in the
+/// same sequence as the received frames.
+fn write_and_record<R: Runtime>(app: &AppHandle<R>, session: &Session, data: Vec<u8>) -> Result<(), String> {
+    let first_byte = Timestamp::now();
+    session.write(&data)?;
+    let last_byte = Timestamp::now();
+    println!("Sent {} bytes to session '{}'", data.len(), session.session_id);
+
+    let record = Record {
+        session_id: session.session_id.clone(),
+        seq: session.next_seq(),
+        direction: Direction::Tx,
+        first_byte,
+        last_byte,
+        data,
+        marker: None,
+    };
+    deliver(app, session, record, None);
+    Ok(())
+}
+
+/// Hand a record to the frontend: through the session's stream if one is
+/// attached and still open, otherwise as a `serial-payload` (or channel)
+/// event.
+fn deliver<R: Runtime>(app: &AppHandle<R>, session: &Session, record: Record, channel: Option<&ChannelLabel>) {
+    let channel_index = channel.map_or(0, |channel| channel.index);
+    match session.lock_history() {
+        Ok(mut history) => history.push(record.clone(), channel_index),
+        Err(e) => println!("{}", e),
+    }
+    if let Ok(log) = session.lock_log() {
+        if let Some(log) = log.as_ref() {
+            log.write(&record, channel_index);
+        }
+    }
+    if let Some(capture) = session.capture() {
+        let port_name = channel.map_or(session.port_name.as_str(), |channel| channel.port_name.as_str());
+        capture.write(&record, channel_index, port_name);
+    }
+    if record.direction == Direction::Rx {
+        emit_receive_matches(app, session, &record, channel_index);
+        if let (None, Some(simulator)) = (channel, session.simulator()) {
+            simulator.on_receive(&record.data);
+        }
     }
 
-    // Signal thread to stop
-    state.should_stop.store(true, Ordering::SeqCst);
-    
-    // Dropping the port closes it
-    *port_lock = None;
-    state.is_connected.store(false, Ordering::SeqCst);
-    println!("Port closed");
+    let record = match session.stream() {
+        Some(stream) => {
+            let len = record.data.len();
+            match stream.push(record, channel_index) {
+                Push::Queued => return,
+                Push::Dropped => {
+                    println!("Stream buffer for session '{}' full, dropped {} bytes", session.session_id, len);
+                    return;
+                }
+                // The frontend went away (e.g. a reload); go back to events
+                // until it attaches a new stream
+                Push::Closed(record) => {
+                    println!("Stream for session '{}' closed, falling back to events", session.session_id);
+                    session.set_stream(None);
+                    record
+                }
+            }
+        }
+        None => record,
+    };
+
+    let result = match channel {
+        Some(channel) => app.emit(channel.event, ChannelPayload {
+            record,
+            channel: channel.index,
+            port_name: channel.port_name.clone(),
+        }),
+        None => app.emit("serial-payload", record),
+    };
+    if let Err(e) = result {
+        let failures = session.emit_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        println!("Failed to emit packet event ({} so far): {}", failures, e);
+    }
+}
 
+fn emit_receive_matches<R: Runtime>(app: &AppHandle<R>, session: &Session, record: &Record, channel: u8) {
+    let matches = match session.lock_matcher() {
+        Ok(mut matcher) => match matcher.as_mut() {
+            Some(matcher) => matcher.scan(channel, &record.data),
+            None => return,
+        },
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    for found in matches {
+        let payload = ReceiveMatchPayload {
+            session_id: session.session_id.clone(),
+            found,
+            channel,
+            seq: record.seq,
+            timestamp: record.last_byte,
+        };
+        if let Err(e) = app.emit("receive-match", payload) {
+            println!("Failed to emit receive-match event: {}", e);
+        }
+    }
+}
+
+/// Look for `receive_commands` in everything the session receives from now
+/// on, emitting `receive-match` for each occurrence. Replaces the sequences
+/// watched so far; returns how many are watched (unparsable ones are
+/// skipped).
+#[tauri::command]
+pub fn start_receive_matching(
+    state: State<'_, SerialState>,
+    session_id: String,
+    receive_commands: Vec<ReceiveCommand>,
+) -> Result<usize, String> {
+    let matcher = ReceiveMatcher::new(&receive_commands)?;
+    let count = matcher.len();
+    *state.sessions.get(&session_id)?.lock_matcher()? = Some(matcher);
+    println!("Session '{}' watching {} receive sequences", session_id, count);
+    Ok(count)
+}
+
+#[tauri::command]
+pub fn stop_receive_matching(state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    *state.sessions.get(&session_id)?.lock_matcher()? = None;
     Ok(())
 }
 
+/// Records kept in the session's history that match `query`.
 #[tauri::command]
-pub fn send_data(state: State<'_, SerialState>, data: Vec<u8>) -> Result<(), String> {
-    let mut port_lock = state.port.lock().map_err(|_| "Failed to lock port mutex")?;
-    
-    if let Some(port) = port_lock.as_mut() {
-        port.write_all(&data)
-            .map_err(|e| format!("Failed to write to port: {}", e))?;
-        port.flush().map_err(|e| format!("Failed to flush port: {}", e))?;
-        println!("Sent {} bytes", data.len());
-        Ok(())
-    } else {
-        Err("Port is not open".to_string())
+pub fn query_history(
+    state: State<'_, SerialState>,
+    session_id: String,
+    query: HistoryQuery,
+) -> Result<Vec<Record>, String> {
+    let session = state.sessions.get(&session_id)?;
+    let history = session.lock_history()?;
+    Ok(history.query(&query))
+}
+
+/// Where `pattern` occurs in the history frames selected by `query`.
+#[tauri::command]
+pub fn search_history(
+    state: State<'_, SerialState>,
+    session_id: String,
+    pattern: Vec<u8>,
+    query: Option<HistoryQuery>,
+) -> Result<Vec<SearchMatch>, String> {
+    let session = state.sessions.get(&session_id)?;
+    let history = session.lock_history()?;
+    Ok(history.search(&pattern, &query.unwrap_or_default()))
+}
+
+#[tauri::command]
+pub fn clear_history(state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    state.sessions.get(&session_id)?.lock_history()?.clear();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_history_info(state: State<'_, SerialState>, session_id: String) -> Result<HistoryInfo, String> {
+    Ok(state.sessions.get(&session_id)?.lock_history()?.info())
+}
+
+/// Change how much history the session keeps; shrinking evicts right away.
+#[tauri::command]
+pub fn set_history_limits(
+    state: State<'_, SerialState>,
+    session_id: String,
+    limits: HistoryLimits,
+) -> Result<(), String> {
+    state.sessions.get(&session_id)?.lock_history()?.set_limits(limits);
+    Ok(())
+}
+
+/// Stream the session's records over `on_data` as binary batches instead of
+/// events. The frontend must `ack_stream` each batch; while it is behind,
+/// up to `buffer_bytes` of data wait and anything beyond is dropped and
+/// counted in the next batch.
+#[tauri::command]
+pub fn attach_stream(
+    state: State<'_, SerialState>,
+    session_id: String,
+    on_data: Channel<InvokeResponseBody>,
+    buffer_bytes: Option<usize>,
+) -> Result<(), String> {
+    let session = state.sessions.get(&session_id)?;
+    let channel_id = on_data.id();
+    let stream = RecordStream::new(buffer_bytes.unwrap_or(DEFAULT_BUFFER_BYTES));
+    spawn_stream_sender(stream.clone(), on_data);
+    session.set_stream(Some(stream));
+    println!("Session '{}' now streams over channel {}", session_id, channel_id);
+    Ok(())
+}
+
+/// Go back to delivering the session's records as events.
+#[tauri::command]
+pub fn detach_stream(state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    state.sessions.get(&session_id)?.set_stream(None);
+    Ok(())
+}
+
+/// The frontend has processed stream batches up to and including `batch_id`.
+#[tauri::command]
+pub fn ack_stream(state: State<'_, SerialState>, session_id: String, batch_id: u64) -> Result<(), String> {
+    let stream = state
+        .sessions
+        .get(&session_id)?
+        .stream()
+        .ok_or_else(|| format!("Session '{}' has no stream attached", session_id))?;
+    stream.ack(batch_id);
+    Ok(())
+}
+
+
+#[tauri::command]
+pub fn list_sessions(state: State<'_, SerialState>) -> Result<Vec<SessionInfo>, String> {
+    Ok(state
+        .sessions
+        .list()?
+        .into_iter()
+        .map(|(session_id, port_name)| SessionInfo { session_id, port_name })
+        .collect())
+}
+
+/// Current state of one session, or of every open session when
+/// `session_id` is omitted. Closed sessions are not listed.
+#[tauri::command]
+pub fn get_connection_status(
+    state: State<'_, SerialState>,
+    session_id: Option<String>,
+) -> Result<Vec<ConnectionStatus>, String> {
+    let sessions = match session_id {
+        Some(session_id) => vec![state.sessions.get(&session_id)?],
+        None => state
+            .sessions
+            .list()?
+            .into_iter()
+            .filter_map(|(session_id, _)| state.sessions.get(&session_id).ok())
+            .collect(),
+    };
+    Ok(sessions
+        .into_iter()
+        .map(|session| ConnectionStatus {
+            session_id: session.session_id.clone(),
+            port_name: session.port_name.clone(),
+            state: session.connection_state(),
+            is_connected: session.is_connected.load(Ordering::SeqCst),
+            emit_failures: session.emit_failures.load(Ordering::SeqCst),
+            dropped_bytes: session.stream().map_or(0, |stream| stream.total_dropped_bytes()),
+        })
+        .collect())
+}
+
+/// Open both ports of a tapped link read-only as one monitoring session.
+/// Each chunk is emitted as a `tap-payload` event labelled with its channel.
+#[tauri::command]
+pub fn open_tap(
+    app: AppHandle,
+    state: State<'_, SerialState>,
+    session_id: String,
+    settings: TapSettings,
+) -> Result<(), String> {
+    ensure_session_free(&state, &session_id)?;
+
+    // Open both before registering anything so a failure on the second port
+    // doesn't leave the first one dangling
+    let mut ports = Vec::with_capacity(2);
+    for config in &settings.channels {
+        let port = SerialTransport::open(config)?;
+        let _ = port.clear_input();
+        ports.push(port);
+    }
+
+    let label = format!("TAP:{}+{}", settings.channels[0].port_name, settings.channels[1].port_name);
+    let session = state.sessions.insert(&session_id, Session::new(&session_id, &label, None))?;
+    session.set_devices(device_paths(&[&settings.channels[0].port_name, &settings.channels[1].port_name]));
+    session.set_connection_state(&app, ConnectionState::Open);
+
+    for (i, (port, config)) in ports.into_iter().zip(settings.channels.iter()).enumerate() {
+        let ctx = ReadContext {
+            app: app.clone(),
+            session_id: session_id.clone(),
+            session: session.clone(),
+            channel: Some(ChannelLabel {
+                event: "tap-payload",
+                index: i as u8 + 1,
+                port_name: config.port_name.clone(),
+            }),
+            forward_to: None,
+            reconnect: None,
+        };
+        spawn_read_thread(ctx, Box::new(port), FramingConfig::default().build()?, session.should_stop.clone());
+    }
+
+    println!("Monitoring session '{}' opened on {}", session_id, label);
+    Ok(())
+}
+
+/// Monitoring settings stored in a loaded project, if it is set up for
+/// monitoring rather than send/receive.
+#[tauri::command]
+pub fn get_tap_settings(project: ProjectData) -> Option<TapSettings> {
+    TapSettings::from_project(&project)
+}
+
+/// Connect two endpoints (serial ports or sockets) and forward bytes between
+/// them in both directions inside the backend. Everything forwarded is also
+/// emitted as `bridge-payload`, labelled with the side it was read from.
+#[tauri::command]
+pub fn open_bridge<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialState>,
+    session_id: String,
+    settings: BridgeSettings,
+) -> Result<(), String> {
+    ensure_session_free(&state, &session_id)?;
+    let framing = settings.framing.unwrap_or_default();
+
+    let mut ports = Vec::with_capacity(2);
+    for config in &settings.endpoints {
+        let port = open_transport(config)?;
+        let _ = port.clear_input();
+        ports.push(port);
+    }
+
+    // Each side's read thread writes into a handle on the opposite side
+    let forward_to = [
+        ports[1].try_clone_transport().map_err(|e| format!("Failed to clone port: {}", e))?,
+        ports[0].try_clone_transport().map_err(|e| format!("Failed to clone port: {}", e))?,
+    ];
+
+    let label = format!("BRIDGE:{}<>{}", settings.endpoints[0].port_name, settings.endpoints[1].port_name);
+    let session = state.sessions.insert(&session_id, Session::new(&session_id, &label, None))?;
+    session.set_devices(device_paths(&[&settings.endpoints[0].port_name, &settings.endpoints[1].port_name]));
+    session.set_connection_state(&app, ConnectionState::Open);
+
+    for (i, ((port, forward_to), config)) in ports
+        .into_iter()
+        .zip(forward_to)
+        .zip(settings.endpoints.iter())
+        .enumerate()
+    {
+        let ctx = ReadContext {
+            app: app.clone(),
+            session_id: session_id.clone(),
+            session: session.clone(),
+            channel: Some(ChannelLabel {
+                event: "bridge-payload",
+                index: i as u8 + 1,
+                port_name: config.port_name.clone(),
+            }),
+            forward_to: Some(forward_to),
+            reconnect: None,
+        };
+        spawn_read_thread(ctx, port, framing.build()?, session.should_stop.clone());
+    }
+
+    println!("Bridge session '{}' opened on {}", session_id, label);
+    Ok(())
+}
+
+/// Drive DTR or RTS on a session's serial port to `level`.
+#[tauri::command]
+pub fn set_modem_line(
+    state: State<'_, SerialState>,
+    session_id: String,
+    line: OutputLine,
+    level: bool,
+) -> Result<(), String> {
+    let session = state.sessions.get(&session_id)?;
+    with_serial_port(&session, |port| write_line(port, line, level))
+}
+
+/// Drive DTR or RTS to `level` for `duration_ms`, then back, e.g. to reset
+/// an Arduino through DTR. Resolves once the line has been restored.
+#[tauri::command]
+pub async fn pulse_modem_line(
+    state: State<'_, SerialState>,
+    session_id: String,
+    line: OutputLine,
+    level: bool,
+    duration_ms: u64,
+) -> Result<(), String> {
+    let session = state.sessions.get(&session_id)?;
+    with_serial_port(&session, |port| write_line(port, line, level))?;
+
+    // Sleep off the async runtime and without holding the port lock, so
+    // sends and other commands keep working during the pulse
+    tauri::async_runtime::spawn_blocking(move || {
+        std::thread::sleep(Duration::from_millis(duration_ms));
+        with_serial_port(&session, |port| write_line(port, line, !level))
+    })
+    .await
+    .map_err(|e| format!("Pulse task failed: {}", e))?
+}
+
+/// Current CTS/DSR/DCD/RI levels, for when the UI needs them before the
+/// next `modem-lines` change event.
+#[tauri::command]
+pub fn get_modem_lines(state: State<'_, SerialState>, session_id: String) -> Result<ModemLines, String> {
+    let session = state.sessions.get(&session_id)?;
+    with_serial_port(&session, |port| {
+        read_lines(port).map_err(|e| format!("Failed to read modem lines: {}", e))
+    })
+}
+
+/// Hold a BREAK condition for `duration_ms`. Marker records show where the
+/// break started and ended in the data stream.
+#[tauri::command]
+pub async fn send_break(
+    app: AppHandle,
+    state: State<'_, SerialState>,
+    session_id: String,
+    duration_ms: u64,
+) -> Result<(), String> {
+    let session = state.sessions.get(&session_id)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        hold_break(&app, &session_id, &session, Duration::from_millis(duration_ms))
+    })
+    .await
+    .map_err(|e| format!("Break task failed: {}", e))?
+}
+
+/// Start a BREAK condition that lasts until `clear_break`.
+#[tauri::command]
+pub fn set_break(app: AppHandle, state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    let session = state.sessions.get(&session_id)?;
+    set_break_level(&app, &session_id, &session, true)
+}
+
+#[tauri::command]
+pub fn clear_break(app: AppHandle, state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    let session = state.sessions.get(&session_id)?;
+    set_break_level(&app, &session_id, &session, false)
+}
+
+/// Send a stored sequence: hex bytes mixed with `BRK` / `BRK:<ms>` steps,
+/// executed in order.
+#[tauri::command]
+pub async fn send_sequence(
+    app: AppHandle,
+    state: State<'_, SerialState>,
+    session_id: String,
+    sequence: String,
+) -> Result<(), String> {
+    let steps = parse_sequence(&sequence)?;
+    let session = state.sessions.get(&session_id)?;
+
+    tauri::async_runtime::spawn_blocking(move || send_steps(&app, &session_id, &session, &steps))
+        .await
+        .map_err(|e| format!("Send task failed: {}", e))?
+}
+
+fn send_steps<R: Runtime>(app: &AppHandle<R>, session_id: &str, session: &Session, steps: &[SendStep]) -> Result<(), String> {
+    for step in steps {
+        match step {
+            SendStep::Data(data) => write_and_record(app, session, data.clone())?,
+            SendStep::Break(duration) => hold_break(app, session_id, session, *duration)?,
+        }
+    }
+    Ok(())
+}
+
+/// Let the session play the device side of `project`: received data that
+/// matches a simulator rule's receive sequence is answered with its send
+/// sequence. Replaces any simulator already running on the session.
+#[tauri::command]
+pub fn start_simulator<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialState>,
+    session_id: String,
+    project: ProjectData,
+) -> Result<(), String> {
+    let session = state.sessions.get(&session_id)?;
+    let answering = session.clone();
+    let simulator = Simulator::start(&project, move |steps| send_steps(&app, &session_id, &answering, steps))?;
+    println!("Session '{}' simulating {} rules", session.session_id, project.simulator_rules.len());
+    session.set_simulator(Some(simulator));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_simulator(state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    state.sessions.get(&session_id)?.set_simulator(None);
+    Ok(())
+}
+
+/// Turn one simulator rule on or off while the simulator runs; `rule` is its
+/// position in the project's simulator rules.
+#[tauri::command]
+pub fn set_simulator_rule(
+    state: State<'_, SerialState>,
+    session_id: String,
+    rule: usize,
+    enabled: bool,
+) -> Result<(), String> {
+    simulator(&state, &session_id)?.set_enabled(rule, enabled)
+}
+
+#[tauri::command]
+pub fn reset_simulator_counters(state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    simulator(&state, &session_id)?.reset_counters();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_simulator_status(state: State<'_, SerialState>, session_id: String) -> Result<Vec<RuleStatus>, String> {
+    Ok(simulator(&state, &session_id)?.status())
+}
+
+fn simulator(state: &SerialState, session_id: &str) -> Result<Arc<Simulator>, String> {
+    state
+        .sessions
+        .get(session_id)?
+        .simulator()
+        .ok_or_else(|| format!("Session '{}' is not simulating", session_id))
+}
+
+/// Switch a replay session between timed playback at `speed` and stepping
+/// frame by frame.
+#[tauri::command]
+pub fn set_replay_mode(state: State<'_, SerialState>, session_id: String, mode: ReplayMode) -> Result<(), String> {
+    replay_control(&*state.sessions.get(&session_id)?)?.set_mode(mode)
+}
+
+/// Release the next `count` frames (default 1) of a replay in step mode.
+#[tauri::command]
+pub fn step_replay(state: State<'_, SerialState>, session_id: String, count: Option<u64>) -> Result<(), String> {
+    replay_control(&*state.sessions.get(&session_id)?)?.step(count.unwrap_or(1))
+}
+
+#[tauri::command]
+pub fn restart_replay(state: State<'_, SerialState>, session_id: String) -> Result<(), String> {
+    replay_control(&*state.sessions.get(&session_id)?)?.restart()
+}
+
+#[tauri::command]
+pub fn get_replay_status(state: State<'_, SerialState>, session_id: String) -> Result<ReplayStatus, String> {
+    replay_control(&*state.sessions.get(&session_id)?)?.status()
+}
+
+fn hold_break<R: Runtime>(app: &AppHandle<R>, session_id: &str, session: &Session, duration: Duration) -> Result<(), String> {
+    set_break_level(app, session_id, session, true)?;
+    // The port lock is released while sleeping so the modem poller and
+    // other commands aren't held up
+    std::thread::sleep(duration);
+    set_break_level(app, session_id, session, false)
+}
+
+fn set_break_level<R: Runtime>(app: &AppHandle<R>, session_id: &str, session: &Session, on: bool) -> Result<(), String> {
+    with_serial_port(session, |port| {
+        let result = if on { port.set_break() } else { port.clear_break() };
+        result.map_err(|e| format!("Failed to {} break: {}", if on { "set" } else { "clear" }, e))
+    })?;
+
+    println!("Session '{}': break {}", session_id, if on { "on" } else { "off" });
+    record_marker(app, session, if on { Marker::BreakOn } else { Marker::BreakOff });
+    Ok(())
+}
+
+/// Deliver a marker record so it lands in the history, log, capture and
+/// frontend in order with the data.
+fn record_marker<R: Runtime>(app: &AppHandle<R>, session: &Session, marker: Marker) {
+    let now = Timestamp::now();
+    let record = Record {
+        session_id: session.session_id.clone(),
+        seq: session.next_seq(),
+        direction: Direction::Tx,
+        first_byte: now,
+        last_byte: now,
+        data: Vec::new(),
+        marker: Some(marker),
+    };
+    deliver(app, session, record, None);
+}
+
+/// Run `f` on a session's serial port, failing for sessions that have none
+/// (network, monitoring and bridge sessions).
+fn with_serial_port<T>(
+    session: &Session,
+    f: impl FnOnce(&mut dyn SerialPort) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut port_lock = session.port.lock().map_err(|_| "Failed to lock port mutex")?;
+    let port = port_lock
+        .as_mut()
+        .and_then(|port| port.serial_port())
+        .ok_or_else(|| format!("Session on {} has no modem lines", session.port_name))?;
+    f(port)
+}
+
+fn replay_control(session: &Session) -> Result<Arc<ReplayControl>, String> {
+    let port_lock = session.port.lock().map_err(|_| "Failed to lock port mutex")?;
+    port_lock
+        .as_ref()
+        .and_then(|port| port.replay_control())
+        .ok_or_else(|| format!("Session on {} is not a replay", session.port_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::config::{FlowControlSetting, ParitySetting, StopBitsSetting};
+    use std::io::{Read, Write};
+    use std::time::Instant;
+    use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+
+    fn mock_app() -> tauri::App<MockRuntime> {
+        mock_builder()
+            .manage(SerialState::new())
+            .build(mock_context(noop_assets()))
+            .unwrap()
+    }
+
+    fn config(port_name: &str) -> SerialConfig {
+        SerialConfig {
+            port_name: port_name.to_string(),
+            baud_rate: 115200,
+            data_bits: 8,
+            stop_bits: StopBitsSetting::One,
+            parity: ParitySetting::None,
+            flow_control: FlowControlSetting::None,
+        }
+    }
+
+    fn line_framing() -> Option<FramingConfig> {
+        Some(FramingConfig::Delimiter { delimiter: b"\n".to_vec(), include_delimiter: true, max_length: 256 })
+    }
+
+    /// The session's history once it holds `count` records, or whatever it
+    /// holds after two seconds.
+    fn wait_for_history(app: &tauri::App<MockRuntime>, session_id: &str, count: usize) -> Vec<Record> {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        loop {
+            let records = query_history(app.state(), session_id.to_string(), HistoryQuery::default()).unwrap();
+            if records.len() >= count || Instant::now() > deadline {
+                return records;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_loopback_session() {
+        let app = mock_app();
+        open_port(app.handle().clone(), app.state(), "loop".to_string(), config("LOOPBACK"), None, line_framing())
+            .unwrap();
+        assert!(open_port(app.handle().clone(), app.state(), "loop".to_string(), config("LOOPBACK"), None, None)
+            .is_err());
+
+        send_data(app.handle().clone(), app.state(), "loop".to_string(), b"AT\r\nATI\r\n".to_vec()).unwrap();
+        let records = wait_for_history(&app, "loop", 3);
+        let summary: Vec<(Direction, &[u8])> =
+            records.iter().map(|record| (record.direction, record.data.as_slice())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Direction::Tx, &b"AT\r\nATI\r\n"[..]),
+                (Direction::Rx, &b"AT\r\n"[..]),
+                (Direction::Rx, &b"ATI\r\n"[..]),
+            ]
+        );
+        assert!(records.windows(2).all(|pair| pair[0].seq < pair[1].seq));
+
+        close_port(app.handle().clone(), app.state(), "loop".to_string()).unwrap();
+        assert!(send_data(app.handle().clone(), app.state(), "loop".to_string(), b"x".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_idle_gap_joins_reads_until_the_line_goes_quiet() {
+        let app = mock_app();
+        let framing = Some(FramingConfig::IdleGap { timeout_ms: 50 });
+        open_port(app.handle().clone(), app.state(), "loop".to_string(), config("LOOPBACK"), None, framing).unwrap();
+
+        // Two writes well inside the gap come back as one frame...
+        send_data(app.handle().clone(), app.state(), "loop".to_string(), b"AT+".to_vec()).unwrap();
+        send_data(app.handle().clone(), app.state(), "loop".to_string(), b"CSQ\r".to_vec()).unwrap();
+        wait_for_history(&app, "loop", 3);
+        // ...and one after it starts the next
+        std::thread::sleep(Duration::from_millis(200));
+        send_data(app.handle().clone(), app.state(), "loop".to_string(), b"ATI\r".to_vec()).unwrap();
+
+        let records = wait_for_history(&app, "loop", 5);
+        let received: Vec<&[u8]> = records
+            .iter()
+            .filter(|record| record.direction == Direction::Rx)
+            .map(|record| record.data.as_slice())
+            .collect();
+        assert_eq!(received, vec![&b"AT+CSQ\r"[..], &b"ATI\r"[..]]);
+
+        close_port(app.handle().clone(), app.state(), "loop".to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_break_markers_in_history() {
+        let app = mock_app();
+        open_port(app.handle().clone(), app.state(), "loop".to_string(), config("LOOPBACK"), None, line_framing())
+            .unwrap();
+        send_data(app.handle().clone(), app.state(), "loop".to_string(), b"AT\n".to_vec()).unwrap();
+        wait_for_history(&app, "loop", 2);
+
+        let session = app.state::<SerialState>().sessions.get("loop").unwrap();
+        record_marker(app.handle(), &session, Marker::BreakOn);
+        record_marker(app.handle(), &session, Marker::BreakOff);
+        let records = wait_for_history(&app, "loop", 4);
+        let markers: Vec<(Option<Marker>, bool)> =
+            records.iter().map(|record| (record.marker, record.data.is_empty())).collect();
+        assert_eq!(
+            markers,
+            vec![(None, false), (None, false), (Some(Marker::BreakOn), true), (Some(Marker::BreakOff), true)]
+        );
+        assert!(records.windows(2).all(|pair| pair[0].seq < pair[1].seq));
+
+        close_port(app.handle().clone(), app.state(), "loop".to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_bridge_forwards_and_records_both_ways() {
+        let app = mock_app();
+        let listeners = [(); 2].map(|_| std::net::TcpListener::bind("127.0.0.1:0").unwrap());
+        let endpoints = listeners
+            .each_ref()
+            .map(|listener| config(&format!("TCP:127.0.0.1:{}", listener.local_addr().unwrap().port())));
+        let settings = BridgeSettings { endpoints, framing: line_framing() };
+        open_bridge(app.handle().clone(), app.state(), "bridge".to_string(), settings).unwrap();
+        let [mut first, mut second] = listeners.map(|listener| listener.accept().unwrap().0);
+        for side in [&first, &second] {
+            side.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        }
+
+        let mut buf = [0u8; 5];
+        first.write_all(b"PING\n").unwrap();
+        second.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"PING\n");
+        second.write_all(b"PONG\n").unwrap();
+        first.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"PONG\n");
+
+        wait_for_history(&app, "bridge", 2);
+        let session = app.state::<SerialState>().sessions.get("bridge").unwrap();
+        let entries = session.lock_history().unwrap().entries(&HistoryQuery::default());
+        let recorded: Vec<(u8, Direction, &[u8])> =
+            entries.iter().map(|(record, channel)| (*channel, record.direction, record.data.as_slice())).collect();
+        assert_eq!(recorded, vec![(1, Direction::Rx, &b"PING\n"[..]), (2, Direction::Rx, &b"PONG\n"[..])]);
+
+        close_port(app.handle().clone(), app.state(), "bridge".to_string()).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_virtual_pair_session() {
+        let app = mock_app();
+        let path = open_virtual_pair(app.handle().clone(), app.state(), "pty".to_string(), line_framing()).unwrap();
+        let mut device = std::fs::OpenOptions::new().read(true).write(true).open(&path).unwrap();
+
+        device.write_all(b"READY\n").unwrap();
+        let records = wait_for_history(&app, "pty", 1);
+        assert_eq!(records.len(), 1);
+        assert_eq!((records[0].direction, records[0].data.as_slice()), (Direction::Rx, &b"READY\n"[..]));
+
+        send_data(app.handle().clone(), app.state(), "pty".to_string(), b"PING\n".to_vec()).unwrap();
+        let mut buf = [0u8; 5];
+        device.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"PING\n");
+
+        close_port(app.handle().clone(), app.state(), "pty".to_string()).unwrap();
     }
 }
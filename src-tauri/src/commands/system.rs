@@ -30,8 +30,171 @@
  * @date 2025-11-19
  */
 
-// Placeholder for system commands
-// Will be implemented in Phase 2
+use crate::commands::serial::{enumerate_ports, PortInfo};
+use crate::serial::state::SerialState;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
 
+/// How often the port list is re-read. Enumeration is cheap, and a second
+/// is quick enough for a picker to feel live.
+const PORT_WATCH_INTERVAL: Duration = Duration::from_secs(1);
 
+#[derive(Clone, Serialize)]
+struct OpenPortRemovedPayload {
+    session_id: String,
+    port: PortInfo,
+}
 
+/// Poll the port list for the lifetime of the app, emitting `port-added` and
+/// `port-removed` with the full `PortInfo` for every change. If a removed
+/// port belongs to an open session, `open-port-removed` is emitted as well.
+pub fn start_port_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut known = enumerate_ports().unwrap_or_default();
+
+        loop {
+            std::thread::sleep(PORT_WATCH_INTERVAL);
+
+            let current = match enumerate_ports() {
+                Ok(ports) => ports,
+                Err(e) => {
+                    println!("Port watcher: {}", e);
+                    continue;
+                }
+            };
+
+            let (added, removed) = diff_ports(&known, &current);
+
+            for port in added {
+                println!("Port added: {}", port.name);
+                if let Err(e) = app.emit("port-added", port) {
+                    println!("Failed to emit port-added event: {}", e);
+                }
+            }
+
+            let sessions = app.state::<SerialState>().sessions.devices().unwrap_or_default();
+            for port in removed {
+                println!("Port removed: {}", port.name);
+                for (session_id, _) in sessions.iter().filter(|(_, devices)| holds_port(devices, &port)) {
+                    let payload = OpenPortRemovedPayload {
+                        session_id: session_id.clone(),
+                        port: port.clone(),
+                    };
+                    if let Err(e) = app.emit("open-port-removed", payload) {
+                        println!("Failed to emit open-port-removed event: {}", e);
+                    }
+                }
+                if let Err(e) = app.emit("port-removed", port) {
+                    println!("Failed to emit port-removed event: {}", e);
+                }
+            }
+
+            known = current;
+        }
+    });
+}
+
+/// Whether a session holding `devices` has `port` open, under its own name
+/// or its by-id link.
+fn holds_port(devices: &[String], port: &PortInfo) -> bool {
+    devices
+        .iter()
+        .any(|device| *device == port.name || Some(device) == port.by_id_path.as_ref())
+}
+
+/// Ports in `current` but not `previous`, and the other way around.
+/// Ports are matched by name, so a port whose metadata changes (e.g. a
+/// driver filling in the product string late) is neither added nor removed.
+fn diff_ports(previous: &[PortInfo], current: &[PortInfo]) -> (Vec<PortInfo>, Vec<PortInfo>) {
+    let missing_from = |ports: &[PortInfo], port: &PortInfo| !ports.iter().any(|p| p.name == port.name);
+    let added = current
+        .iter()
+        .filter(|p| missing_from(previous, p))
+        .cloned()
+        .collect();
+    let removed = previous
+        .iter()
+        .filter(|p| missing_from(current, p))
+        .cloned()
+        .collect();
+    (added, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(name: &str, product: Option<&str>) -> PortInfo {
+        PortInfo {
+            name: name.to_string(),
+            description: None,
+            port_type: "usb".to_string(),
+            vid: Some(0x0403),
+            pid: Some(0x6001),
+            serial_number: None,
+            manufacturer: None,
+            product: product.map(str::to_string),
+            by_id_path: None,
+        }
+    }
+
+    #[test]
+    fn test_holds_port_matches_every_device_of_a_session() {
+        // A tap opened through by-id links on both sides
+        let tap = vec![
+            "/dev/ttyUSB0".to_string(),
+            "/dev/serial/by-id/usb-FTDI_FT232R_A1-if00-port0".to_string(),
+            "/dev/ttyUSB1".to_string(),
+        ];
+        assert!(holds_port(&tap, &port("/dev/ttyUSB1", None)));
+        assert!(holds_port(&tap, &PortInfo {
+            by_id_path: Some("/dev/serial/by-id/usb-FTDI_FT232R_A1-if00-port0".to_string()),
+            ..port("/dev/ttyUSB7", None)
+        }));
+        assert!(!holds_port(&tap, &port("/dev/ttyACM0", None)));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_bridge_session_holds_its_serial_side() {
+        use crate::commands::serial::{close_port, open_bridge};
+        use crate::serial::bridge::BridgeSettings;
+        use crate::serial::config::SerialConfig;
+        use crate::serial::virtual_port::PtyTransport;
+        use tauri::test::{mock_builder, mock_context, noop_assets};
+
+        let app = mock_builder().manage(SerialState::new()).build(mock_context(noop_assets())).unwrap();
+        let device = PtyTransport::open().unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let network = format!("TCP:127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let settings = BridgeSettings {
+            endpoints: [SerialConfig::new(device.path(), 115200), SerialConfig::new(&network, 115200)],
+            framing: None,
+        };
+        open_bridge(app.handle().clone(), app.state(), "bridge".to_string(), settings).unwrap();
+
+        let sessions = app.state::<SerialState>().sessions.devices().unwrap();
+        let holding: Vec<&str> = sessions
+            .iter()
+            .filter(|(_, devices)| holds_port(devices, &port(device.path(), None)))
+            .map(|(session_id, _)| session_id.as_str())
+            .collect();
+        assert_eq!(holding, vec!["bridge"]);
+
+        close_port(app.handle().clone(), app.state(), "bridge".to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_diff_ports_by_name() {
+        let previous = vec![port("/dev/ttyUSB0", None), port("/dev/ttyUSB1", None)];
+        let current = vec![port("/dev/ttyUSB0", Some("FT232R USB UART")), port("/dev/ttyACM0", None)];
+
+        let (added, removed) = diff_ports(&previous, &current);
+        assert_eq!(added, vec![port("/dev/ttyACM0", None)]);
+        assert_eq!(removed, vec![port("/dev/ttyUSB1", None)]);
+
+        let (added, removed) = diff_ports(&current, &current);
+        assert!(added.is_empty() && removed.is_empty());
+    }
+}
@@ -45,11 +45,49 @@ fn main() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .manage(SerialState::new())
+        .setup(|app| {
+            commands::system::start_port_watcher(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::serial::list_ports,
+            commands::serial::get_line_capabilities,
             commands::serial::open_port,
+            commands::serial::listen_port,
+            commands::serial::open_virtual_pair,
             commands::serial::close_port,
             commands::serial::send_data,
+            commands::serial::list_sessions,
+            commands::serial::get_connection_status,
+            commands::serial::attach_stream,
+            commands::serial::detach_stream,
+            commands::serial::ack_stream,
+            commands::serial::query_history,
+            commands::serial::search_history,
+            commands::serial::clear_history,
+            commands::serial::get_history_info,
+            commands::serial::set_history_limits,
+            commands::serial::open_tap,
+            commands::serial::get_tap_settings,
+            commands::serial::open_bridge,
+            commands::serial::set_modem_line,
+            commands::serial::pulse_modem_line,
+            commands::serial::get_modem_lines,
+            commands::serial::send_break,
+            commands::serial::set_break,
+            commands::serial::clear_break,
+            commands::serial::send_sequence,
+            commands::serial::start_receive_matching,
+            commands::serial::stop_receive_matching,
+            commands::serial::start_simulator,
+            commands::serial::stop_simulator,
+            commands::serial::set_simulator_rule,
+            commands::serial::reset_simulator_counters,
+            commands::serial::get_simulator_status,
+            commands::serial::set_replay_mode,
+            commands::serial::step_replay,
+            commands::serial::restart_replay,
+            commands::serial::get_replay_status,
             commands::file::save_project,
             commands::file::load_project,
             commands::file::save_project_dialog,
@@ -57,6 +95,13 @@ fn main() {
             commands::file::get_recent_projects,
             commands::file::add_recent_project,
             commands::file::clear_recent_projects,
+            commands::log::start_log,
+            commands::log::stop_log,
+            commands::log::pause_log,
+            commands::log::get_log_status,
+            commands::pcap::export_pcapng,
+            commands::pcap::start_capture,
+            commands::pcap::stop_capture,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
@@ -64,8 +109,8 @@ fn main() {
     app.run(|app_handle, event| {
         if let tauri::RunEvent::ExitRequested { .. } = event {
             let state = app_handle.state::<SerialState>();
-            if let Err(e) = commands::serial::close_port(state) {
-                eprintln!("Failed to close port on exit: {}", e);
+            if let Err(e) = state.sessions.close_all() {
+                eprintln!("Failed to close ports on exit: {}", e);
             }
         }
     });
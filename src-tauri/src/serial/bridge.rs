@@ -0,0 +1,46 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file bridge.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+use crate::serial::config::SerialConfig;
+use crate::serial::framing::FramingConfig;
+use serde::{Deserialize, Serialize};
+
+/// The two sides of a bridge session. Each endpoint's `port_name` uses the
+/// same syntax as `open_port` (`COM3`, `TCP:host:port`, `SERVER:port`, ...);
+/// its line settings apply if it is a serial port. `framing` only decides how
+/// the forwarded bytes are grouped into records; forwarding itself never
+/// waits for a frame to complete.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeSettings {
+    pub endpoints: [SerialConfig; 2],
+    #[serde(default)]
+    pub framing: Option<FramingConfig>,
+}
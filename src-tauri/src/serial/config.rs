@@ -30,8 +30,285 @@
  * @date 2025-11-19
  */
 
-// Placeholder for serial configuration
-// Will be implemented in Phase 2
+use serde::{Deserialize, Serialize};
+use serialport::{DataBits, FlowControl, Parity, SerialPortBuilder, StopBits};
 
+/// Everything needed to open a serial port, as sent by the frontend's
+/// `SerialConfig`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialConfig {
+    pub port_name: String,
+    pub baud_rate: u32,
+    pub data_bits: u8,
+    pub stop_bits: StopBitsSetting,
+    pub parity: ParitySetting,
+    #[serde(default)]
+    pub flow_control: FlowControlSetting,
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "f32", into = "f32")]
+pub enum StopBitsSetting {
+    One,
+    OnePointFive,
+    Two,
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParitySetting {
+    None,
+    Even,
+    Odd,
+    /// Parity bit always 1
+    Mark,
+    /// Parity bit always 0
+    Space,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FlowControlSetting {
+    #[default]
+    None,
+    /// RTS/CTS
+    Hardware,
+    /// XON/XOFF
+    Software,
+}
+
+/// The parity and stop bit settings `SerialConfig::validate` accepts on this
+/// platform, so the frontend only offers settings that can work.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineCapabilities {
+    pub parity: Vec<ParitySetting>,
+    pub stop_bits: Vec<StopBitsSetting>,
+}
+
+impl LineCapabilities {
+    pub fn current() -> Self {
+        let mut parity = vec![ParitySetting::None, ParitySetting::Even, ParitySetting::Odd];
+        if MARK_SPACE_SUPPORTED {
+            parity.extend([ParitySetting::Mark, ParitySetting::Space]);
+        }
+        let mut stop_bits = vec![StopBitsSetting::One];
+        if ONE_POINT_FIVE_SUPPORTED {
+            stop_bits.push(StopBitsSetting::OnePointFive);
+        }
+        stop_bits.push(StopBitsSetting::Two);
+        Self { parity, stop_bits }
+    }
+}
+
+/// Mark and space parity are set up through termios, which only Linux
+/// exposes (see `apply_mark_space`).
+const MARK_SPACE_SUPPORTED: bool = cfg!(target_os = "linux");
+
+/// Linux UARTs send 1.5 stop bits when CSTOPB is set with CS5, so asking
+/// for two stop bits on a 5-bit line gets 1.5. Other drivers don't say.
+const ONE_POINT_FIVE_SUPPORTED: bool = cfg!(target_os = "linux");
+
+impl TryFrom<f32> for StopBitsSetting {
+    type Error = String;
+
+    fn try_from(value: f32) -> Result<Self, Self::Error> {
+        if value == 1.0 {
+            Ok(StopBitsSetting::One)
+        } else if value == 1.5 {
+            Ok(StopBitsSetting::OnePointFive)
+        } else if value == 2.0 {
+            Ok(StopBitsSetting::Two)
+        } else {
+            Err(format!("Invalid stop bits: {} (expected 1, 1.5 or 2)", value))
+        }
+    }
+}
+
+impl From<StopBitsSetting> for f32 {
+    fn from(value: StopBitsSetting) -> Self {
+        match value {
+            StopBitsSetting::One => 1.0,
+            StopBitsSetting::OnePointFive => 1.5,
+            StopBitsSetting::Two => 2.0,
+        }
+    }
+}
+
+impl SerialConfig {
+    /// 8N1 without flow control, the default for monitoring and bridging.
+    pub fn new(port_name: &str, baud_rate: u32) -> Self {
+        Self {
+            port_name: port_name.to_string(),
+            baud_rate,
+            data_bits: 8,
+            stop_bits: StopBitsSetting::One,
+            parity: ParitySetting::None,
+            flow_control: FlowControlSetting::None,
+        }
+    }
+
+    /// Check the combination before touching the port so the user gets a
+    /// specific reason instead of a driver error.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.baud_rate == 0 {
+            return Err("Baud rate must be greater than 0".to_string());
+        }
+
+        self.data_bits()?;
+
+        if self.stop_bits == StopBitsSetting::OnePointFive {
+            // UARTs only define 1.5 stop bits for 5-bit characters
+            if self.data_bits != 5 {
+                return Err("1.5 stop bits are only valid with 5 data bits".to_string());
+            }
+            if !ONE_POINT_FIVE_SUPPORTED {
+                return Err("1.5 stop bits are not supported on this platform (use 1 or 2)".to_string());
+            }
+        }
+
+        if matches!(self.parity, ParitySetting::Mark | ParitySetting::Space) && !MARK_SPACE_SUPPORTED {
+            return Err(format!("{:?} parity is not supported on this platform", self.parity));
+        }
+
+        Ok(())
+    }
+
+    /// Builder for everything `serialport` can express directly. Mark and
+    /// space parity are requested as even/odd here and finished off by
+    /// `apply_mark_space` once the port is open.
+    pub fn builder(&self) -> Result<SerialPortBuilder, String> {
+        self.validate()?;
+
+        // With 5 data bits the UART turns two stop bits into 1.5
+        let stop_bits = match self.stop_bits {
+            StopBitsSetting::One => StopBits::One,
+            StopBitsSetting::OnePointFive | StopBitsSetting::Two => StopBits::Two,
+        };
+
+        let parity = match self.parity {
+            ParitySetting::None => Parity::None,
+            ParitySetting::Even | ParitySetting::Space => Parity::Even,
+            ParitySetting::Odd | ParitySetting::Mark => Parity::Odd,
+        };
+
+        let flow_control = match self.flow_control {
+            FlowControlSetting::None => FlowControl::None,
+            FlowControlSetting::Hardware => FlowControl::Hardware,
+            FlowControlSetting::Software => FlowControl::Software,
+        };
+
+        Ok(serialport::new(&self.port_name, self.baud_rate)
+            .data_bits(self.data_bits()?)
+            .stop_bits(stop_bits)
+            .parity(parity)
+            .flow_control(flow_control))
+    }
+
+    fn data_bits(&self) -> Result<DataBits, String> {
+        match self.data_bits {
+            5 => Ok(DataBits::Five),
+            6 => Ok(DataBits::Six),
+            7 => Ok(DataBits::Seven),
+            8 => Ok(DataBits::Eight),
+            n => Err(format!("Invalid data bits: {} (expected 5 to 8)", n)),
+        }
+    }
+}
+
+/// Switch an open port from even/odd to space/mark ("stick") parity.
+#[cfg(target_os = "linux")]
+pub fn apply_mark_space(fd: std::os::unix::io::RawFd, parity: ParitySetting) -> Result<(), String> {
+    let mark = match parity {
+        ParitySetting::Mark => true,
+        ParitySetting::Space => false,
+        _ => return Ok(()),
+    };
+
+    // SAFETY: `fd` is an open tty owned by the caller for the whole call, and
+    // termios is plain data that tcgetattr fully initializes
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut termios) != 0 {
+            return Err(format!("Failed to read port attributes: {}", std::io::Error::last_os_error()));
+        }
+        termios.c_cflag |= libc::PARENB | libc::CMSPAR;
+        if mark {
+            termios.c_cflag |= libc::PARODD;
+        } else {
+            termios.c_cflag &= !libc::PARODD;
+        }
+        if libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0 {
+            return Err(format!("Failed to set {:?} parity: {}", parity, std::io::Error::last_os_error()));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serial_config_from_frontend_json() {
+        let config: SerialConfig = serde_json::from_str(
+            r#"{"portName":"COM3","baudRate":9600,"dataBits":7,"stopBits":2,"parity":"Even","flowControl":"Hardware"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.stop_bits, StopBitsSetting::Two);
+        assert_eq!(config.parity, ParitySetting::Even);
+        assert_eq!(config.flow_control, FlowControlSetting::Hardware);
+        assert!(config.validate().is_ok());
+
+        // Older frontends don't send flow control
+        let config: SerialConfig = serde_json::from_str(
+            r#"{"portName":"COM3","baudRate":9600,"dataBits":8,"stopBits":1.5,"parity":"None"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.flow_control, FlowControlSetting::None);
+        assert_eq!(config.validate().unwrap_err(), "1.5 stop bits are only valid with 5 data bits");
+
+        assert!(serde_json::from_str::<SerialConfig>(
+            r#"{"portName":"COM3","baudRate":9600,"dataBits":8,"stopBits":3,"parity":"None"}"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_serial_config_validate() {
+        let mut config = SerialConfig::new("COM1", 115200);
+        assert!(config.validate().is_ok());
+
+        config.data_bits = 9;
+        assert!(config.validate().is_err());
+
+        config.data_bits = 8;
+        config.stop_bits = StopBitsSetting::OnePointFive;
+        assert!(config.validate().unwrap_err().contains("only valid with 5 data bits"));
+
+        config.data_bits = 5;
+        if ONE_POINT_FIVE_SUPPORTED {
+            assert!(config.validate().is_ok());
+            assert!(config.builder().is_ok());
+        } else {
+            assert!(config.validate().unwrap_err().contains("not supported"));
+        }
+
+        config.baud_rate = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_line_capabilities_validate() {
+        let capabilities = LineCapabilities::current();
+        for &parity in &capabilities.parity {
+            for &stop_bits in &capabilities.stop_bits {
+                // 1.5 stop bits only exist for 5-bit characters
+                let data_bits = if stop_bits == StopBitsSetting::OnePointFive { 5 } else { 8 };
+                let config = SerialConfig { parity, stop_bits, data_bits, ..SerialConfig::new("COM1", 9600) };
+                assert_eq!(config.validate(), Ok(()), "{:?} parity, {:?} stop bits", parity, stop_bits);
+            }
+        }
+        assert_eq!(capabilities.stop_bits.contains(&StopBitsSetting::OnePointFive), ONE_POINT_FIVE_SUPPORTED);
+    }
+}
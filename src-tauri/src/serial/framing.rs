@@ -0,0 +1,399 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file framing.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Frames longer than this are cut, so a missing delimiter or a corrupt
+/// length field can't grow the buffer without bound
+pub const DEFAULT_MAX_FRAME: usize = 64 * 1024;
+
+/// How the receive path splits the byte stream into frames. Each frame is
+/// emitted as one payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FramingConfig {
+    /// Bytes arriving less than `timeout_ms` apart belong to the same frame
+    IdleGap { timeout_ms: u64 },
+    /// Frames end with `delimiter`, e.g. `[13, 10]` for CR LF
+    Delimiter {
+        delimiter: Vec<u8>,
+        /// Keep the delimiter at the end of each frame
+        #[serde(default = "default_true")]
+        include_delimiter: bool,
+        #[serde(default = "default_max_frame")]
+        max_length: usize,
+    },
+    /// Every frame is exactly `length` bytes
+    FixedLength { length: usize },
+    /// Frames carry their own length in a header field of `length_size`
+    /// bytes at `length_offset`. The whole frame is
+    /// `length_offset + length_size + value + length_adjustment` bytes.
+    LengthPrefixed {
+        #[serde(default)]
+        length_offset: usize,
+        length_size: u8,
+        #[serde(default)]
+        big_endian: bool,
+        #[serde(default)]
+        length_adjustment: i64,
+        #[serde(default = "default_max_frame")]
+        max_length: usize,
+    },
+    /// Every read is passed on as it arrives
+    Raw,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_frame() -> usize {
+    DEFAULT_MAX_FRAME
+}
+
+impl Default for FramingConfig {
+    fn default() -> Self {
+        FramingConfig::IdleGap { timeout_ms: 5 }
+    }
+}
+
+impl FramingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            FramingConfig::IdleGap { .. } | FramingConfig::Raw => Ok(()),
+            FramingConfig::Delimiter { delimiter, max_length, .. } => {
+                if delimiter.is_empty() {
+                    return Err("Frame delimiter must not be empty".to_string());
+                }
+                if *max_length < delimiter.len() {
+                    return Err(format!("Maximum frame length {} is shorter than the delimiter", max_length));
+                }
+                Ok(())
+            }
+            FramingConfig::FixedLength { length } => {
+                if *length == 0 {
+                    return Err("Frame length must be at least 1 byte".to_string());
+                }
+                Ok(())
+            }
+            FramingConfig::LengthPrefixed { length_offset, length_size, max_length, .. } => {
+                if !matches!(length_size, 1 | 2 | 4) {
+                    return Err(format!("Invalid length field size: {} (expected 1, 2 or 4 bytes)", length_size));
+                }
+                // Otherwise no frame could ever hold its own header
+                let header = length_offset + *length_size as usize;
+                if *max_length < header {
+                    return Err(format!(
+                        "Maximum frame length {} is shorter than the {}-byte length header",
+                        max_length, header
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn build(&self) -> Result<Box<dyn Framer>, String> {
+        self.validate()?;
+        Ok(match self.clone() {
+            FramingConfig::IdleGap { timeout_ms } => Box::new(IdleGapFramer {
+                timeout: Duration::from_millis(timeout_ms),
+                buffer: Vec::new(),
+            }),
+            FramingConfig::Delimiter { delimiter, include_delimiter, max_length } => Box::new(DelimiterFramer {
+                delimiter,
+                include_delimiter,
+                max_length,
+                buffer: Vec::new(),
+            }),
+            FramingConfig::FixedLength { length } => Box::new(FixedLengthFramer { length, buffer: Vec::new() }),
+            FramingConfig::LengthPrefixed { length_offset, length_size, big_endian, length_adjustment, max_length } => {
+                Box::new(LengthPrefixedFramer {
+                    length_offset,
+                    length_size: length_size as usize,
+                    big_endian,
+                    length_adjustment,
+                    max_length,
+                    buffer: Vec::new(),
+                })
+            }
+            FramingConfig::Raw => Box::new(RawFramer),
+        })
+    }
+}
+
+/// Splits received bytes into frames. Framers only see bytes; the read
+/// thread handles timing through `idle_timeout`.
+pub trait Framer: Send {
+    /// Add received bytes, returning the frames they complete.
+    fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>>;
+
+    /// Hand over whatever is buffered as a (possibly partial) frame.
+    fn flush(&mut self) -> Option<Vec<u8>>;
+
+    fn has_pending(&self) -> bool;
+
+    /// Quiet time after which buffered bytes are flushed as a frame
+    fn idle_timeout(&self) -> Option<Duration> {
+        None
+    }
+}
+
+fn take_buffer(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    (!buffer.is_empty()).then(|| std::mem::take(buffer))
+}
+
+struct IdleGapFramer {
+    timeout: Duration,
+    buffer: Vec<u8>,
+}
+
+impl Framer for IdleGapFramer {
+    fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        self.buffer.extend_from_slice(data);
+        Vec::new()
+    }
+
+    fn flush(&mut self) -> Option<Vec<u8>> {
+        take_buffer(&mut self.buffer)
+    }
+
+    fn has_pending(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    fn idle_timeout(&self) -> Option<Duration> {
+        Some(self.timeout)
+    }
+}
+
+struct DelimiterFramer {
+    delimiter: Vec<u8>,
+    include_delimiter: bool,
+    max_length: usize,
+    buffer: Vec<u8>,
+}
+
+impl Framer for DelimiterFramer {
+    fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        let mut frames = Vec::new();
+        for &byte in data {
+            self.buffer.push(byte);
+            if self.buffer.ends_with(&self.delimiter) {
+                let mut frame = std::mem::take(&mut self.buffer);
+                if !self.include_delimiter {
+                    frame.truncate(frame.len() - self.delimiter.len());
+                }
+                frames.push(frame);
+            } else if self.buffer.len() >= self.max_length {
+                frames.push(std::mem::take(&mut self.buffer));
+            }
+        }
+        frames
+    }
+
+    fn flush(&mut self) -> Option<Vec<u8>> {
+        take_buffer(&mut self.buffer)
+    }
+
+    fn has_pending(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+}
+
+struct FixedLengthFramer {
+    length: usize,
+    buffer: Vec<u8>,
+}
+
+impl Framer for FixedLengthFramer {
+    fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        self.buffer.extend_from_slice(data);
+        let mut frames = Vec::new();
+        while self.buffer.len() >= self.length {
+            let rest = self.buffer.split_off(self.length);
+            frames.push(std::mem::replace(&mut self.buffer, rest));
+        }
+        frames
+    }
+
+    fn flush(&mut self) -> Option<Vec<u8>> {
+        take_buffer(&mut self.buffer)
+    }
+
+    fn has_pending(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+}
+
+struct LengthPrefixedFramer {
+    length_offset: usize,
+    length_size: usize,
+    big_endian: bool,
+    length_adjustment: i64,
+    max_length: usize,
+    buffer: Vec<u8>,
+}
+
+impl LengthPrefixedFramer {
+    /// Total length of the frame at the start of the buffer, once its
+    /// header has arrived. `Err` means the header can't be valid.
+    fn frame_length(&self) -> Option<Result<usize, ()>> {
+        let header_end = self.length_offset + self.length_size;
+        let field = self.buffer.get(self.length_offset..header_end)?;
+        let value = if self.big_endian {
+            field.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64)
+        } else {
+            field.iter().rev().fold(0u64, |acc, &b| (acc << 8) | b as u64)
+        };
+        let total = header_end as i64 + value as i64 + self.length_adjustment;
+        Some(if total < header_end as i64 || total as usize > self.max_length {
+            Err(())
+        } else {
+            Ok(total as usize)
+        })
+    }
+}
+
+impl Framer for LengthPrefixedFramer {
+    fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        self.buffer.extend_from_slice(data);
+        let mut frames = Vec::new();
+        while let Some(length) = self.frame_length() {
+            match length {
+                Ok(length) if self.buffer.len() >= length => {
+                    let rest = self.buffer.split_off(length);
+                    frames.push(std::mem::replace(&mut self.buffer, rest));
+                }
+                Ok(_) => break,
+                // Not a frame start: pass the byte on by itself and try
+                // again from the next one
+                Err(()) => frames.push(self.buffer.drain(..1).collect()),
+            }
+        }
+        frames
+    }
+
+    fn flush(&mut self) -> Option<Vec<u8>> {
+        take_buffer(&mut self.buffer)
+    }
+
+    fn has_pending(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+}
+
+struct RawFramer;
+
+impl Framer for RawFramer {
+    fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        vec![data.to_vec()]
+    }
+
+    fn flush(&mut self) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn has_pending(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framer(json: &str) -> Box<dyn Framer> {
+        serde_json::from_str::<FramingConfig>(json).unwrap().build().unwrap()
+    }
+
+    #[test]
+    fn test_delimiter_and_fixed_length_framing() {
+        let mut lines = framer(r#"{"type":"delimiter","delimiter":[13,10]}"#);
+        assert_eq!(lines.push(b"OK\r"), Vec::<Vec<u8>>::new());
+        assert_eq!(lines.push(b"\nERR\r\nPART"), vec![b"OK\r\n".to_vec(), b"ERR\r\n".to_vec()]);
+        assert_eq!(lines.flush(), Some(b"PART".to_vec()));
+
+        let mut fixed = framer(r#"{"type":"fixed_length","length":3}"#);
+        assert_eq!(fixed.push(b"abcdefg"), vec![b"abc".to_vec(), b"def".to_vec()]);
+        assert!(fixed.has_pending());
+
+        assert!(serde_json::from_str::<FramingConfig>(r#"{"type":"delimiter","delimiter":[]}"#)
+            .unwrap()
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_length_prefixed_framing() {
+        // STX, 16-bit big-endian payload length, payload, checksum byte
+        let mut stx = framer(
+            r#"{"type":"length_prefixed","length_offset":1,"length_size":2,"big_endian":true,"length_adjustment":1}"#,
+        );
+        let frames = stx.push(&[0x02, 0x00, 0x02, 0xAA, 0xBB, 0x55, 0x02, 0x00]);
+        assert_eq!(frames, vec![vec![0x02, 0x00, 0x02, 0xAA, 0xBB, 0x55]]);
+        assert_eq!(stx.push(&[0x00, 0x11]), vec![vec![0x02, 0x00, 0x00, 0x11]]);
+        assert!(!stx.has_pending());
+
+        // A length beyond max_length can't be a header, so resync byte by byte
+        let mut short = framer(r#"{"type":"length_prefixed","length_size":1,"max_length":4}"#);
+        assert_eq!(short.push(&[0xFF, 0x01, 0x42]), vec![vec![0xFF], vec![0x01, 0x42]]);
+
+        let no_room: FramingConfig =
+            serde_json::from_str(r#"{"type":"length_prefixed","length_offset":3,"length_size":2,"max_length":4}"#)
+                .unwrap();
+        assert!(no_room.validate().unwrap_err().contains("shorter than the 5-byte length header"));
+    }
+
+    #[test]
+    fn test_idle_gap_and_raw_framing() {
+        // Reads only add to the frame; the read thread flushes it once the
+        // line has been quiet for the timeout
+        let mut idle = framer(r#"{"type":"idle_gap","timeout_ms":20}"#);
+        assert_eq!(idle.idle_timeout(), Some(Duration::from_millis(20)));
+        assert!(!idle.has_pending());
+        assert_eq!(idle.push(b"AT+C"), Vec::<Vec<u8>>::new());
+        assert_eq!(idle.push(b"SQ\r"), Vec::<Vec<u8>>::new());
+        assert!(idle.has_pending());
+        assert_eq!(idle.flush(), Some(b"AT+CSQ\r".to_vec()));
+        assert_eq!(idle.flush(), None);
+
+        // Each read is a frame as it arrived, and nothing is ever held back
+        let mut raw = framer(r#"{"type":"raw"}"#);
+        assert_eq!(raw.idle_timeout(), None);
+        assert_eq!(raw.push(b"AT"), vec![b"AT".to_vec()]);
+        assert_eq!(raw.push(b"\r\n"), vec![b"\r\n".to_vec()]);
+        assert!(!raw.has_pending());
+        assert_eq!(raw.flush(), None);
+    }
+}
@@ -0,0 +1,235 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file history.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use crate::serial::record::{Direction, Record};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// How much traffic a session keeps. The oldest frames are evicted once
+/// either limit is exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryLimits {
+    pub max_bytes: usize,
+    pub max_frames: usize,
+}
+
+impl Default for HistoryLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 16 * 1024 * 1024,
+            max_frames: 100_000,
+        }
+    }
+}
+
+/// Which records a history query returns. Every bound is optional and
+/// inclusive; `limit` caps the result, keeping the oldest matches.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HistoryQuery {
+    pub from_seq: Option<u64>,
+    pub to_seq: Option<u64>,
+    pub from_time: Option<DateTime<Local>>,
+    pub to_time: Option<DateTime<Local>>,
+    pub direction: Option<Direction>,
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    fn matches(&self, record: &Record) -> bool {
+        let time = record.first_byte.wall_clock;
+        self.from_seq.is_none_or(|from| record.seq >= from)
+            && self.to_seq.is_none_or(|to| record.seq <= to)
+            && self.from_time.is_none_or(|from| time >= from)
+            && self.to_time.is_none_or(|to| time <= to)
+            && self.direction.is_none_or(|direction| record.direction == direction)
+    }
+}
+
+/// A search hit: `offset` is where the pattern starts in frame `seq`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchMatch {
+    pub seq: u64,
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryInfo {
+    pub frames: usize,
+    pub bytes: usize,
+    pub first_seq: Option<u64>,
+    pub last_seq: Option<u64>,
+    /// Frames pushed out by the limits since the session opened or was cleared
+    pub evicted_frames: u64,
+    pub limits: HistoryLimits,
+}
+
+/// Bounded record of a session's traffic in both directions, kept in
+/// arrival order. Each record keeps the channel it came from (0 for
+/// single-port sessions).
+pub struct History {
+    limits: HistoryLimits,
+    records: VecDeque<(Record, u8)>,
+    bytes: usize,
+    evicted_frames: u64,
+}
+
+impl History {
+    pub fn new(limits: HistoryLimits) -> Self {
+        Self {
+            limits,
+            records: VecDeque::new(),
+            bytes: 0,
+            evicted_frames: 0,
+        }
+    }
+
+    pub fn push(&mut self, record: Record, channel: u8) {
+        self.bytes += record.data.len();
+        self.records.push_back((record, channel));
+        self.evict();
+    }
+
+    pub fn set_limits(&mut self, limits: HistoryLimits) {
+        self.limits = limits;
+        self.evict();
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.bytes = 0;
+        self.evicted_frames = 0;
+    }
+
+    pub fn query(&self, query: &HistoryQuery) -> Vec<Record> {
+        self.entries(query).into_iter().map(|(record, _)| record).collect()
+    }
+
+    /// Like `query`, with the channel of each record.
+    pub fn entries(&self, query: &HistoryQuery) -> Vec<(Record, u8)> {
+        self.records
+            .iter()
+            .filter(|(record, _)| query.matches(record))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    /// Find `pattern` in the frames selected by `query`. Matches don't span
+    /// frame boundaries.
+    pub fn search(&self, pattern: &[u8], query: &HistoryQuery) -> Vec<SearchMatch> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        self.records
+            .iter()
+            .filter(|(record, _)| query.matches(record))
+            .flat_map(|(record, _)| {
+                record
+                    .data
+                    .windows(pattern.len())
+                    .enumerate()
+                    .filter(|(_, window)| *window == pattern)
+                    .map(|(offset, _)| SearchMatch { seq: record.seq, offset })
+            })
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    pub fn info(&self) -> HistoryInfo {
+        HistoryInfo {
+            frames: self.records.len(),
+            bytes: self.bytes,
+            first_seq: self.records.front().map(|(record, _)| record.seq),
+            last_seq: self.records.back().map(|(record, _)| record.seq),
+            evicted_frames: self.evicted_frames,
+            limits: self.limits,
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.records.len() > self.limits.max_frames || self.bytes > self.limits.max_bytes {
+            let Some((oldest, _)) = self.records.pop_front() else {
+                break;
+            };
+            self.bytes -= oldest.data.len();
+            self.evicted_frames += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::record::Timestamp;
+
+    fn record(seq: u64, direction: Direction, data: &[u8]) -> Record {
+        let now = Timestamp::now();
+        Record {
+            session_id: "main".to_string(),
+            seq,
+            direction,
+            first_byte: now,
+            last_byte: now,
+            data: data.to_vec(),
+            marker: None,
+        }
+    }
+
+    #[test]
+    fn test_history_limits_and_queries() {
+        let mut history = History::new(HistoryLimits { max_bytes: 10, max_frames: 3 });
+        history.push(record(0, Direction::Tx, b"AT\r"), 0);
+        history.push(record(1, Direction::Rx, b"OK\r"), 0);
+        history.push(record(2, Direction::Tx, b"AT+X\r"), 0);
+        // Over the byte limit: frame 0 goes
+        assert_eq!(history.info().first_seq, Some(1));
+        assert_eq!(history.info().bytes, 8);
+        history.push(record(3, Direction::Rx, b"OK\r"), 0);
+        // Now over both; frame 1 goes too
+        assert_eq!(history.info().frames, 2);
+        assert_eq!(history.info().evicted_frames, 2);
+
+        let rx = history.query(&HistoryQuery { direction: Some(Direction::Rx), ..Default::default() });
+        assert_eq!(rx.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![3]);
+        let from_two = history.query(&HistoryQuery { from_seq: Some(2), limit: Some(1), ..Default::default() });
+        assert_eq!(from_two[0].seq, 2);
+
+        let hits = history.search(b"\r", &HistoryQuery::default());
+        assert_eq!(hits, vec![SearchMatch { seq: 2, offset: 4 }, SearchMatch { seq: 3, offset: 2 }]);
+
+        history.clear();
+        assert_eq!(history.info().frames, 0);
+    }
+}
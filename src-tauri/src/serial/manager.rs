@@ -30,8 +30,109 @@
  * @date 2025-11-19
  */
 
-// Placeholder for port management
-// Will be implemented in Phase 2
+use crate::serial::state::Session;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
 
+/// Open sessions keyed by the id the frontend chose when opening them.
+///
+/// The map lock is only held long enough to look a session up; I/O happens
+/// on the session's own port lock so one busy channel never stalls another.
+pub struct SessionManager {
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+}
 
+impl SessionManager {
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
 
+    pub fn contains(&self, session_id: &str) -> Result<bool, String> {
+        Ok(self.lock()?.contains_key(session_id))
+    }
+
+    pub fn get(&self, session_id: &str) -> Result<Arc<Session>, String> {
+        self.lock()?
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| format!("Session '{}' is not open", session_id))
+    }
+
+    pub fn insert(&self, session_id: &str, session: Session) -> Result<Arc<Session>, String> {
+        let mut sessions = self.lock()?;
+        if sessions.contains_key(session_id) {
+            return Err(format!("Session '{}' is already open", session_id));
+        }
+        let session = Arc::new(session);
+        sessions.insert(session_id.to_string(), session.clone());
+        Ok(session)
+    }
+
+    /// Remove a session and signal its read thread to stop. The port closes
+    /// once the last handle (usually the read thread's clone) is dropped.
+    pub fn close(&self, session_id: &str) -> Result<bool, String> {
+        let session = self.lock()?.remove(session_id);
+        match session {
+            Some(session) => {
+                session.should_stop.store(true, Ordering::SeqCst);
+                session.is_connected.store(false, Ordering::SeqCst);
+                session.set_stream(None);
+                session.set_log(None);
+                session.set_capture(None);
+                session.set_simulator(None);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Like `close`, but only if `session_id` still refers to `session`,
+    /// so a read thread winding down can't close a newer session that
+    /// reused its id.
+    pub fn close_if_current(&self, session_id: &str, session: &Arc<Session>) -> Result<bool, String> {
+        let is_current = self
+            .lock()?
+            .get(session_id)
+            .is_some_and(|current| Arc::ptr_eq(current, session));
+        if is_current {
+            self.close(session_id)
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub fn close_all(&self) -> Result<(), String> {
+        let ids: Vec<String> = self.lock()?.keys().cloned().collect();
+        for id in ids {
+            self.close(&id)?;
+        }
+        Ok(())
+    }
+
+    /// Ids and port names of every open session.
+    pub fn list(&self) -> Result<Vec<(String, String)>, String> {
+        Ok(self
+            .lock()?
+            .iter()
+            .map(|(id, session)| (id.clone(), session.port_name.clone()))
+            .collect())
+    }
+
+    /// Ids of every open session with the device paths it holds.
+    pub fn devices(&self) -> Result<Vec<(String, Vec<String>)>, String> {
+        Ok(self
+            .lock()?
+            .iter()
+            .map(|(id, session)| (id.clone(), session.devices()))
+            .collect())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, Arc<Session>>>, String> {
+        self.sessions
+            .lock()
+            .map_err(|_| "Failed to lock session map".to_string())
+    }
+}
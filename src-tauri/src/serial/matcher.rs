@@ -0,0 +1,440 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file matcher.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use crate::commands::file::ReceiveCommand;
+use crate::serial::sequence::{parse_receive_sequence, ReceiveByte};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+const ROOT: u32 = 0;
+
+/// Upper bound on automaton states; wildcards can multiply them, and each
+/// takes 1 KiB of transitions.
+const MAX_STATES: usize = 8192;
+
+/// Progress through one pattern: (pattern, positions consumed)
+type Item = (u32, u32);
+
+/// Aho-Corasick style automaton over a set of receive sequences. It is
+/// built as a DFA straight from the patterns, which gives the classic
+/// goto/failure automaton for plain bytes and also handles the `?` and `#`
+/// wildcards. Transitions are a dense table, so scanning costs one lookup
+/// per byte no matter how many patterns there are.
+pub struct Automaton {
+    patterns: Vec<Vec<ReceiveByte>>,
+    /// 256 entries per state
+    next: Vec<u32>,
+    /// Patterns completed on entering each state
+    outputs: Vec<Vec<usize>>,
+    /// Longest possible match, i.e. how many recent bytes a scan keeps
+    max_len: usize,
+}
+
+/// Where a scan of one byte stream stands, kept between reads so patterns
+/// split across packets still match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanState {
+    state: u32,
+    /// Bytes scanned so far
+    offset: u64,
+    /// The last `max_len` bytes, to recover what a match covered
+    recent: VecDeque<u8>,
+    /// (pattern, start offset) of matches that could still be extended by
+    /// a `#`, so an occurrence is reported once rather than once per length
+    reported: VecDeque<(usize, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamMatch {
+    pub pattern: usize,
+    /// Position of the first matched byte in the stream
+    pub offset: u64,
+    pub data: Vec<u8>,
+    /// What each wildcard of the pattern matched, in order; `None` for a
+    /// `#` that matched no byte
+    pub wildcards: Vec<Option<u8>>,
+}
+
+impl Automaton {
+    /// Build the automaton; matches report a pattern by its position in
+    /// `patterns`. Empty patterns never match.
+    pub fn new(patterns: &[Vec<ReceiveByte>]) -> Result<Self, String> {
+        // Items every state implicitly contains: the start of each pattern,
+        // since a match can begin at any byte
+        let start = closure(patterns, (0..patterns.len() as u32).filter(|&p| !patterns[p as usize].is_empty()).map(|p| (p, 0)).collect());
+
+        // States are keyed by their items beyond `start`
+        let mut keys: Vec<Vec<Item>> = vec![Vec::new()];
+        let mut ids: HashMap<Vec<Item>, u32> = HashMap::from([(Vec::new(), ROOT)]);
+        let mut next = Vec::new();
+        let mut outputs = Vec::new();
+
+        let mut state = 0;
+        while state < keys.len() {
+            let items: Vec<Item> = keys[state].iter().chain(&start).copied().collect();
+            outputs.push(
+                keys[state]
+                    .iter()
+                    .filter(|&&(p, k)| k as usize == patterns[p as usize].len())
+                    .map(|&(p, _)| p as usize)
+                    .collect(),
+            );
+
+            for byte in 0..=255u8 {
+                let stepped = items
+                    .iter()
+                    .filter(|&&(p, k)| {
+                        patterns[p as usize].get(k as usize).is_some_and(|expected| match expected {
+                            ReceiveByte::Byte(b) => *b == byte,
+                            ReceiveByte::Any | ReceiveByte::Optional => true,
+                        })
+                    })
+                    .map(|&(p, k)| (p, k + 1))
+                    .collect();
+                let mut key = closure(patterns, stepped);
+                key.retain(|item| start.binary_search(item).is_err());
+
+                let id = match ids.get(&key) {
+                    Some(&id) => id,
+                    None => {
+                        if keys.len() == MAX_STATES {
+                            return Err("Receive sequences are too complex to match (too many wildcards)".to_string());
+                        }
+                        let id = keys.len() as u32;
+                        ids.insert(key.clone(), id);
+                        keys.push(key);
+                        id
+                    }
+                };
+                next.push(id);
+            }
+            state += 1;
+        }
+
+        Ok(Self {
+            patterns: patterns.to_vec(),
+            next,
+            outputs,
+            max_len: patterns.iter().map(Vec::len).max().unwrap_or(0),
+        })
+    }
+
+    /// Feed the next chunk of a stream, calling `on_match` for every pattern
+    /// occurrence that ends in it, overlapping ones included. An occurrence
+    /// a `#` lets end in more than one place (`41 42 #` on "ABx") is
+    /// reported once, at the first place it ends.
+    pub fn scan(&self, scan: &mut ScanState, data: &[u8], mut on_match: impl FnMut(StreamMatch)) {
+        for &byte in data {
+            scan.state = self.next[scan.state as usize * 256 + byte as usize];
+            scan.offset += 1;
+            scan.recent.push_back(byte);
+            if scan.recent.len() > self.max_len {
+                scan.recent.pop_front();
+            }
+
+            let outputs = &self.outputs[scan.state as usize];
+            if outputs.is_empty() {
+                continue;
+            }
+            // Nothing that started before the window can match again
+            let window_start = scan.offset.saturating_sub(self.max_len as u64);
+            scan.reported.retain(|&(_, start)| start >= window_start);
+
+            for &pattern in outputs {
+                if let Some((data, wildcards)) = align(&self.patterns[pattern], &scan.recent) {
+                    let offset = scan.offset - data.len() as u64;
+                    if scan.reported.contains(&(pattern, offset)) {
+                        continue;
+                    }
+                    scan.reported.push_back((pattern, offset));
+                    on_match(StreamMatch { pattern, offset, data, wildcards });
+                }
+            }
+        }
+    }
+}
+
+/// Add the items reachable by skipping `#` wildcards, sorted and deduplicated.
+fn closure(patterns: &[Vec<ReceiveByte>], mut items: Vec<Item>) -> Vec<Item> {
+    let mut i = 0;
+    while i < items.len() {
+        let (p, k) = items[i];
+        if patterns[p as usize].get(k as usize) == Some(&ReceiveByte::Optional) && !items.contains(&(p, k + 1)) {
+            items.push((p, k + 1));
+        }
+        i += 1;
+    }
+    items.sort_unstable();
+    items.dedup();
+    items
+}
+
+/// Find the shortest way `pattern` covers the end of `recent`, returning the
+/// matched bytes and what each wildcard took.
+fn align(pattern: &[ReceiveByte], recent: &VecDeque<u8>) -> Option<(Vec<u8>, Vec<Option<u8>>)> {
+    // Walk backwards from the last byte, preferring to let `#` match nothing
+    fn walk(pattern: &[ReceiveByte], recent: &VecDeque<u8>, end: usize, fills: &mut Vec<Option<u8>>) -> Option<usize> {
+        let Some((last, rest)) = pattern.split_last() else {
+            return Some(end);
+        };
+        let byte = end.checked_sub(1).map(|i| recent[i]);
+        match (last, byte) {
+            (ReceiveByte::Byte(expected), Some(b)) if *expected == b => walk(rest, recent, end - 1, fills),
+            (ReceiveByte::Any, Some(b)) => {
+                fills.push(Some(b));
+                walk(rest, recent, end - 1, fills).or_else(|| {
+                    fills.pop();
+                    None
+                })
+            }
+            (ReceiveByte::Optional, _) => {
+                fills.push(None);
+                if let Some(start) = walk(rest, recent, end, fills) {
+                    return Some(start);
+                }
+                fills.pop();
+                let b = byte?;
+                fills.push(Some(b));
+                walk(rest, recent, end - 1, fills).or_else(|| {
+                    fills.pop();
+                    None
+                })
+            }
+            _ => None,
+        }
+    }
+
+    let mut fills = Vec::new();
+    let start = walk(pattern, recent, recent.len(), &mut fills)?;
+    fills.reverse();
+    Some((recent.range(start..).copied().collect(), fills))
+}
+
+/// A receive sequence found in the data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReceiveMatch {
+    pub command_index: i32,
+    pub name: String,
+    /// Position of the first matched byte among all bytes received on the
+    /// channel since matching started
+    pub offset: u64,
+    pub data: Vec<u8>,
+    /// Bytes that filled the sequence's `?` and `#` wildcards, in order;
+    /// `null` for a `#` that matched nothing
+    pub wildcards: Vec<Option<u8>>,
+}
+
+/// Watches a session's received data for a project's receive sequences,
+/// with a separate scan per channel.
+pub struct ReceiveMatcher {
+    automaton: Automaton,
+    commands: Vec<(i32, String)>,
+    channels: HashMap<u8, ScanState>,
+}
+
+impl ReceiveMatcher {
+    /// Sequences that don't parse are left out with a warning, so one bad
+    /// entry doesn't disable the rest.
+    pub fn new(receive_commands: &[ReceiveCommand]) -> Result<Self, String> {
+        let mut patterns = Vec::new();
+        let mut commands = Vec::new();
+        for cmd in receive_commands {
+            match parse_receive_sequence(&cmd.hex_data) {
+                Ok(pattern) if !pattern.is_empty() => {
+                    patterns.push(pattern);
+                    commands.push((cmd.index, cmd.name.clone()));
+                }
+                Ok(_) => println!("Receive sequence '{}' is empty, not matching it", cmd.name),
+                Err(e) => println!("Receive sequence '{}' not matched: {}", cmd.name, e),
+            }
+        }
+
+        Ok(Self {
+            automaton: Automaton::new(&patterns)?,
+            commands,
+            channels: HashMap::new(),
+        })
+    }
+
+    /// Number of receive sequences being looked for.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn scan(&mut self, channel: u8, data: &[u8]) -> Vec<ReceiveMatch> {
+        let mut matches = Vec::new();
+        let scan = self.channels.entry(channel).or_default();
+        self.automaton.scan(scan, data, |found| {
+            let (command_index, name) = &self.commands[found.pattern];
+            matches.push(ReceiveMatch {
+                command_index: *command_index,
+                name: name.clone(),
+                offset: found.offset,
+                data: found.data,
+                wildcards: found.wildcards,
+            });
+        });
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::file::parse_project_file;
+    use std::io::Write;
+
+    fn literal(text: &str) -> Vec<ReceiveByte> {
+        text.bytes().map(ReceiveByte::Byte).collect()
+    }
+
+    #[test]
+    fn test_automaton_matches_across_chunks() {
+        let patterns: Vec<Vec<ReceiveByte>> = ["he", "she", "his", "hers", ""].into_iter().map(literal).collect();
+        let automaton = Automaton::new(&patterns).unwrap();
+
+        // "ushers" split mid-pattern, then "his" split over three chunks
+        let mut scan = ScanState::default();
+        let mut found = Vec::new();
+        for chunk in [&b"ush"[..], b"ers h", b"i", b"s"] {
+            automaton.scan(&mut scan, chunk, |m| found.push((m.pattern, m.offset)));
+        }
+        found.sort();
+        assert_eq!(found, vec![(0, 2), (1, 1), (2, 7), (3, 2)]);
+
+        let mut scan = ScanState::default();
+        let mut count = 0;
+        automaton.scan(&mut scan, &b"x".repeat(10_000), |_| count += 1);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_optional_wildcard_reports_each_occurrence_once() {
+        use ReceiveByte::{Byte, Optional};
+        let patterns = vec![vec![Byte(b'A'), Byte(b'B'), Optional], vec![Byte(b'C'), Optional, Byte(b'D')]];
+        let automaton = Automaton::new(&patterns).unwrap();
+
+        let mut scan = ScanState::default();
+        let mut found = Vec::new();
+        for chunk in [&b"ABx"[..], b"ABAB", b"CDD"] {
+            automaton.scan(&mut scan, chunk, |m| found.push((m.pattern, m.offset, m.data, m.wildcards)));
+        }
+        // A trailing `#` settles on matching nothing, and "CDD" doesn't also
+        // report the longer match from the same start
+        assert_eq!(
+            found,
+            vec![
+                (0, 0, b"AB".to_vec(), vec![None]),
+                (0, 3, b"AB".to_vec(), vec![None]),
+                (0, 5, b"AB".to_vec(), vec![None]),
+                (1, 7, b"CD".to_vec(), vec![None]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_docklight_wildcards() {
+        // RECEIVE entries as Docklight writes them to a .ptp project
+        let content = "VERSION
+8
+
+COMMSETTINGS
+0
+COM3
+COM2
+57600
+2
+63
+4
+0
+0
+
+COMMDISPLAY
+0
+
+RECEIVE
+0
+Position
+24 47 50 47 47 41 2C ?? ?? ?? ?? ?? ?? 2C
+0
+5
+COMMENT \"time of fix is wildcarded\"
+0
+0
+0
+0
+
+RECEIVE
+1
+Temperature
+54 3D # ?? 2E ?? 0D 0A
+0
+5
+COMMENT \"one or two digits before the point\"
+0
+0
+0
+0
+
+RECEIVE
+2
+Error
+45 52 52 ## 0D
+0
+6
+COMMENT \"\"
+0
+0
+0
+0
+";
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "{}", content).unwrap();
+        let project = parse_project_file(file.path()).unwrap();
+        let mut matcher = ReceiveMatcher::new(&project.receive_commands).unwrap();
+        assert_eq!(matcher.len(), 3);
+
+        let mut found = matcher.scan(0, b"$GPGGA,1234");
+        found.extend(matcher.scan(0, b"56,4807.038,N*47\r\nT=7.5\r\nT=21.0\r\nERR\r"));
+        let positions: Vec<(i32, u64)> = found.iter().map(|m| (m.command_index, m.offset)).collect();
+        assert_eq!(positions, vec![(0, 0), (1, 29), (1, 36), (2, 44)]);
+        assert_eq!(found[0].data, b"$GPGGA,123456,");
+        assert_eq!(found[0].wildcards, b"123456".iter().copied().map(Some).collect::<Vec<_>>());
+        assert_eq!(found[1].data, b"T=7.5\r\n");
+        assert_eq!(found[1].wildcards, vec![None, Some(b'7'), Some(b'5')]);
+        assert_eq!(found[2].wildcards, vec![Some(b'2'), Some(b'1'), Some(b'0')]);
+        assert_eq!((found[3].data.as_slice(), found[3].wildcards.as_slice()), (&b"ERR\r"[..], &[None][..]));
+
+        // Other channels are scanned separately
+        assert!(matcher.scan(1, b"2,\r\n").is_empty());
+    }
+}
@@ -33,3 +33,16 @@ pub mod port;
 pub mod config;
 pub mod manager;
 pub mod state;
+pub mod bridge;
+pub mod framing;
+pub mod history;
+pub mod matcher;
+pub mod modem;
+pub mod reconnect;
+pub mod record;
+pub mod replay;
+pub mod sequence;
+pub mod simulator;
+pub mod stream;
+pub mod tap;
+pub mod virtual_port;
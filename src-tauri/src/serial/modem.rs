@@ -0,0 +1,129 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file modem.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use crate::serial::port::Transport;
+use chrono::{Local, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use serialport::SerialPort;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// How often the poller samples the input lines. Fast enough to catch a
+/// modem dropping carrier, slow enough not to matter for CPU.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Output lines the host drives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputLine {
+    #[serde(rename = "DTR")]
+    Dtr,
+    #[serde(rename = "RTS")]
+    Rts,
+}
+
+/// Input line levels as reported by the UART.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ModemLines {
+    pub cts: bool,
+    pub dsr: bool,
+    pub dcd: bool,
+    pub ri: bool,
+}
+
+#[derive(Clone, Serialize)]
+struct ModemLinesPayload {
+    session_id: String,
+    #[serde(flatten)]
+    lines: ModemLines,
+    /// Local time the change was seen
+    timestamp: String,
+}
+
+pub fn write_line(port: &mut dyn SerialPort, line: OutputLine, level: bool) -> Result<(), String> {
+    let result = match line {
+        OutputLine::Dtr => port.write_data_terminal_ready(level),
+        OutputLine::Rts => port.write_request_to_send(level),
+    };
+    result.map_err(|e| format!("Failed to set {:?}: {}", line, e))
+}
+
+pub fn read_lines(port: &mut dyn SerialPort) -> serialport::Result<ModemLines> {
+    Ok(ModemLines {
+        cts: port.read_clear_to_send()?,
+        dsr: port.read_data_set_ready()?,
+        dcd: port.read_carrier_detect()?,
+        ri: port.read_ring_indicator()?,
+    })
+}
+
+/// Watch the input lines of a session's serial port on its own handle and
+/// emit `modem-lines` whenever any of them changes, starting with the state
+/// at open. Stops with the session or once the lines can't be read anymore.
+pub fn spawn_modem_poller<R: Runtime>(
+    app: AppHandle<R>,
+    session_id: String,
+    mut port: Box<dyn Transport>,
+    should_stop: Arc<AtomicBool>,
+) {
+    std::thread::spawn(move || {
+        let mut last: Option<ModemLines> = None;
+
+        while !should_stop.load(Ordering::SeqCst) {
+            let Some(serial) = port.serial_port() else {
+                break;
+            };
+
+            match read_lines(serial) {
+                Ok(lines) if last != Some(lines) => {
+                    last = Some(lines);
+                    let payload = ModemLinesPayload {
+                        session_id: session_id.clone(),
+                        lines,
+                        timestamp: Local::now().to_rfc3339_opts(SecondsFormat::Micros, false),
+                    };
+                    if let Err(e) = app.emit("modem-lines", payload) {
+                        println!("Failed to emit modem-lines event: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    println!("Stopping modem line poller for '{}': {}", session_id, e);
+                    break;
+                }
+            }
+
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
@@ -30,8 +30,624 @@
  * @date 2025-11-19
  */
 
-// Placeholder for serial port handler
-// Will be implemented in Phase 2
+use crate::serial::config::SerialConfig;
+use crate::serial::replay::ReplayControl;
+use serialport::SerialPort;
+use std::io::{self, Read, Write};
+use std::collections::VecDeque;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+/// Read timeout shared by every transport so the read thread can poll
+/// `should_stop` at the same cadence regardless of the channel type.
+pub const READ_TIMEOUT: Duration = Duration::from_millis(100);
 
+const TCP_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 
+/// A byte stream the read thread and `send_data` can drive without knowing
+/// whether it is backed by a serial port or a network socket.
+///
+/// Reads must time out with `ErrorKind::TimedOut` when no data arrives, and
+/// any other error means the channel is gone.
+pub trait Transport: Read + Write + Send {
+    /// Create an independent handle to the same channel for the read thread.
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>>;
+
+    /// Discard anything buffered on the receive side.
+    fn clear_input(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Whether each successful read returns one complete message, so the
+    /// read thread should emit it as-is instead of grouping by idle gap.
+    fn is_datagram(&self) -> bool {
+        false
+    }
+
+    /// The underlying serial port, for modem-line control that only exists on
+    /// real UARTs.
+    fn serial_port(&mut self) -> Option<&mut dyn SerialPort> {
+        None
+    }
+
+    /// Next peer change noticed while reading, for transports where the
+    /// remote side can come and go without the channel closing.
+    fn take_peer_event(&mut self) -> Option<PeerEvent> {
+        None
+    }
+
+    /// Change how long a read waits for data. The read thread shortens it to
+    /// wake up exactly when an idle-gap frame is due; transports that can't
+    /// change it keep `READ_TIMEOUT`. `timeout` is never zero.
+    fn set_read_timeout(&mut self, _timeout: Duration) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Playback controls, for sessions replaying a capture.
+    fn replay_control(&self) -> Option<Arc<ReplayControl>> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerEvent {
+    Connected(SocketAddr),
+    Disconnected(SocketAddr),
+}
+
+/// Where a channel points to, parsed from the same strings Docklight stores
+/// in `COMMCHANNELS` (`TCP:host:port`, `UDP:host:port[:local_port]`,
+/// `SERVER:port`) or a plain serial port name. `REPLAY:path` plays back a
+/// capture file and `LOOPBACK` echoes writes, neither needing hardware.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Serial(String),
+    Tcp { host: String, port: u16 },
+    /// Datagrams go to `host:port`; we listen on `local_port`, or on an
+    /// ephemeral port (0) when none is given.
+    Udp { host: String, port: u16, local_port: u16 },
+    /// Listen on `port` and use whichever client connects as the channel.
+    TcpServer { port: u16 },
+    /// Play back a pcapng capture or termLight log file.
+    Replay(String),
+    Loopback,
+}
+
+impl Endpoint {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        let mut parts = name.splitn(2, ':');
+        let scheme = parts.next().unwrap_or_default();
+
+        match (scheme.to_ascii_uppercase().as_str(), parts.next()) {
+            ("TCP", Some(target)) => {
+                let (host, port) = split_host_port(target)?;
+                Ok(Endpoint::Tcp { host, port })
+            }
+            ("UDP", Some(target)) => {
+                // An optional trailing local port makes this host:port:local_port,
+                // which is ambiguous with a bare IPv6 host unless it's bracketed
+                let (target, local_port) = match target.rsplit_once(':') {
+                    Some((rest, local)) if rest.contains(':') && !rest.ends_with(']') => {
+                        let local_port = local
+                            .parse()
+                            .map_err(|_| format!("Invalid local port '{}'", local))?;
+                        (rest, Some(local_port))
+                    }
+                    _ => (target, None),
+                };
+                let (host, port) = split_host_port(target)?;
+                Ok(Endpoint::Udp { host, port, local_port: local_port.unwrap_or(0) })
+            }
+            ("SERVER", Some(port)) => {
+                let port = port
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid port '{}'", port))?;
+                Ok(Endpoint::TcpServer { port })
+            }
+            ("REPLAY", Some(path)) => Ok(Endpoint::Replay(path.trim().to_string())),
+            ("LOOPBACK", None) => Ok(Endpoint::Loopback),
+            _ => Ok(Endpoint::Serial(name.to_string())),
+        }
+    }
+}
+
+/// Split `host:port`, accepting bracketed IPv6 literals (`[::1]:5000`).
+fn split_host_port(target: &str) -> Result<(String, u16), String> {
+    let (host, port) = target
+        .rsplit_once(':')
+        .ok_or_else(|| format!("Missing port in '{}' (expected host:port)", target))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return Err(format!("Missing host in '{}'", target));
+    }
+    let port = port
+        .parse()
+        .map_err(|_| format!("Invalid port '{}'", port))?;
+    Ok((host.to_string(), port))
+}
+
+// ============================================================================
+// Serial
+// ============================================================================
+
+pub struct SerialTransport {
+    port: Box<dyn SerialPort>,
+}
+
+impl SerialTransport {
+    pub fn open(config: &SerialConfig) -> Result<Self, String> {
+        let builder = config.builder()?.timeout(READ_TIMEOUT);
+
+        #[cfg(target_os = "linux")]
+        let port: Box<dyn SerialPort> = {
+            use std::os::unix::io::AsRawFd;
+            let port = builder
+                .open_native()
+                .map_err(|e| format!("Failed to open port: {}", e))?;
+            crate::serial::config::apply_mark_space(port.as_raw_fd(), config.parity)?;
+            Box::new(port)
+        };
+
+        #[cfg(not(target_os = "linux"))]
+        let port = builder
+            .open()
+            .map_err(|e| format!("Failed to open port: {}", e))?;
+
+        // Clear any stale data in the receive buffer
+        let _ = port.clear(serialport::ClearBuffer::Input);
+
+        // Give the device a moment to settle after port open
+        std::thread::sleep(Duration::from_millis(50));
+
+        Ok(Self { port })
+    }
+}
+
+impl Read for SerialTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.port.read(buf)
+    }
+}
+
+impl Write for SerialTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.port.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+impl Transport for SerialTransport {
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>> {
+        let port = self.port.try_clone().map_err(io::Error::other)?;
+        Ok(Box::new(SerialTransport { port }))
+    }
+
+    fn clear_input(&self) -> io::Result<()> {
+        self.port
+            .clear(serialport::ClearBuffer::Input)
+            .map_err(io::Error::other)
+    }
+
+    fn serial_port(&mut self) -> Option<&mut dyn SerialPort> {
+        Some(self.port.as_mut())
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.port.set_timeout(timeout).map_err(io::Error::other)
+    }
+}
+
+// ============================================================================
+// TCP client
+// ============================================================================
+
+pub struct TcpTransport {
+    stream: TcpStream,
+}
+
+impl TcpTransport {
+    pub fn connect(host: &str, port: u16) -> Result<Self, String> {
+        let addrs = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| format!("Failed to resolve {}:{}: {}", host, port, e))?;
+
+        let mut last_err = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, TCP_CONNECT_TIMEOUT) {
+                Ok(stream) => return Self::from_stream(stream),
+                Err(e) => last_err = Some(e),
+            }
+        }
+
+        Err(match last_err {
+            Some(e) => format!("Failed to connect to {}:{}: {}", host, port, e),
+            None => format!("No addresses found for {}:{}", host, port),
+        })
+    }
+
+    pub fn from_stream(stream: TcpStream) -> Result<Self, String> {
+        stream
+            .set_read_timeout(Some(READ_TIMEOUT))
+            .map_err(|e| format!("Failed to configure socket: {}", e))?;
+        // Commands are usually short; don't let Nagle hold them back
+        let _ = stream.set_nodelay(true);
+        Ok(Self { stream })
+    }
+}
+
+impl Read for TcpTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.stream.read(buf) {
+            // A zero-length read on a socket means the peer closed it
+            Ok(0) if !buf.is_empty() => Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "Connection closed by remote host",
+            )),
+            // Unix reports an expired read timeout as WouldBlock
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Err(io::Error::from(io::ErrorKind::TimedOut))
+            }
+            other => other,
+        }
+    }
+}
+
+impl Write for TcpTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl Transport for TcpTransport {
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(TcpTransport {
+            stream: self.stream.try_clone()?,
+        }))
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.stream.set_read_timeout(Some(timeout))
+    }
+}
+
+// ============================================================================
+// TCP server
+// ============================================================================
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Listens on a local port and serves one client at a time. When the client
+/// drops, the next one to connect takes over without closing the channel;
+/// clients arriving meanwhile wait in the listen backlog.
+///
+/// Only the handle given to the read thread accepts and reads; every clone
+/// writes to whichever client is currently connected.
+pub struct TcpServerTransport {
+    listener: TcpListener,
+    client: Arc<Mutex<Option<TcpStream>>>,
+    reader: Option<(TcpStream, SocketAddr)>,
+    events: VecDeque<PeerEvent>,
+}
+
+impl TcpServerTransport {
+    pub fn bind(port: u16) -> Result<Self, String> {
+        let listener = TcpListener::bind((std::net::Ipv4Addr::UNSPECIFIED, port))
+            .map_err(|e| format!("Failed to listen on TCP port {}: {}", port, e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure socket: {}", e))?;
+
+        Ok(Self {
+            listener,
+            client: Arc::new(Mutex::new(None)),
+            reader: None,
+            events: VecDeque::new(),
+        })
+    }
+
+    /// Wait up to `READ_TIMEOUT` for a client, making it the active one.
+    fn accept_client(&mut self) -> io::Result<()> {
+        let deadline = std::time::Instant::now() + READ_TIMEOUT;
+        loop {
+            match self.listener.accept() {
+                Ok((stream, addr)) => {
+                    // Some platforms hand out sockets inheriting the listener's
+                    // non-blocking flag
+                    stream.set_nonblocking(false)?;
+                    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                    let _ = stream.set_nodelay(true);
+
+                    *self.lock_client()? = Some(stream.try_clone()?);
+                    self.reader = Some((stream, addr));
+                    self.events.push_back(PeerEvent::Connected(addr));
+                    return Ok(());
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    if std::time::Instant::now() >= deadline {
+                        return Err(io::Error::from(io::ErrorKind::TimedOut));
+                    }
+                    std::thread::sleep(ACCEPT_POLL_INTERVAL);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn drop_client(&mut self) {
+        if let Some((_, addr)) = self.reader.take() {
+            if let Ok(mut client) = self.client.lock() {
+                *client = None;
+            }
+            self.events.push_back(PeerEvent::Disconnected(addr));
+        }
+    }
+
+    fn lock_client(&self) -> io::Result<std::sync::MutexGuard<'_, Option<TcpStream>>> {
+        self.client
+            .lock()
+            .map_err(|_| io::Error::other("Failed to lock client mutex"))
+    }
+}
+
+impl Read for TcpServerTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.reader.is_none() {
+            self.accept_client()?;
+        }
+
+        let Some((stream, _)) = self.reader.as_mut() else {
+            return Err(io::Error::from(io::ErrorKind::TimedOut));
+        };
+
+        match stream.read(buf) {
+            Ok(n) if n > 0 || buf.is_empty() => Ok(n),
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                Err(io::Error::from(io::ErrorKind::TimedOut))
+            }
+            // EOF or a reset only ends this client, not the session
+            _ => {
+                self.drop_client();
+                Err(io::Error::from(io::ErrorKind::TimedOut))
+            }
+        }
+    }
+}
+
+impl Write for TcpServerTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.lock_client()?.as_mut() {
+            Some(stream) => stream.write(buf),
+            None => Err(io::Error::new(io::ErrorKind::NotConnected, "No client connected")),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.lock_client()?.as_mut() {
+            Some(stream) => stream.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Transport for TcpServerTransport {
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(TcpServerTransport {
+            listener: self.listener.try_clone()?,
+            client: self.client.clone(),
+            reader: None,
+            events: VecDeque::new(),
+        }))
+    }
+
+    fn take_peer_event(&mut self) -> Option<PeerEvent> {
+        self.events.pop_front()
+    }
+}
+
+impl Drop for TcpServerTransport {
+    fn drop(&mut self) {
+        // The reader owns the accepted connection; shut it down so the client
+        // notices instead of lingering on a half-open socket
+        if let Some((stream, _)) = self.reader.take() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+    }
+}
+
+// ============================================================================
+// UDP
+// ============================================================================
+
+/// Largest payload a UDP datagram can carry.
+pub const MAX_DATAGRAM_SIZE: usize = 65507;
+
+pub struct UdpTransport {
+    socket: UdpSocket,
+    remote: SocketAddr,
+}
+
+impl UdpTransport {
+    pub fn open(host: &str, port: u16, local_port: u16) -> Result<Self, String> {
+        let remote = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| format!("Failed to resolve {}:{}: {}", host, port, e))?
+            .next()
+            .ok_or_else(|| format!("No addresses found for {}:{}", host, port))?;
+
+        let bind_addr: SocketAddr = if remote.is_ipv6() {
+            (std::net::Ipv6Addr::UNSPECIFIED, local_port).into()
+        } else {
+            (std::net::Ipv4Addr::UNSPECIFIED, local_port).into()
+        };
+
+        let socket = UdpSocket::bind(bind_addr)
+            .map_err(|e| format!("Failed to bind UDP port {}: {}", local_port, e))?;
+        socket
+            .set_read_timeout(Some(READ_TIMEOUT))
+            .map_err(|e| format!("Failed to configure socket: {}", e))?;
+
+        Ok(Self { socket, remote })
+    }
+}
+
+impl Read for UdpTransport {
+    /// Receives one datagram from `remote`. Datagrams from anyone else are
+    /// dropped and reported as a timeout so the read loop just carries on.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.socket.recv_from(buf) {
+            Ok((n, source)) if source == self.remote => Ok(n),
+            // Not logged: any host could flood the log this way
+            Ok(_) => Err(io::Error::from(io::ErrorKind::TimedOut)),
+            Err(e) => match e.kind() {
+                // Windows surfaces ICMP "port unreachable" from an earlier send
+                // as a reset on the next receive; that's not fatal for UDP
+                io::ErrorKind::WouldBlock | io::ErrorKind::ConnectionReset => {
+                    Err(io::Error::from(io::ErrorKind::TimedOut))
+                }
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+impl Write for UdpTransport {
+    /// Sends `buf` as a single datagram.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.socket.send_to(buf, self.remote)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for UdpTransport {
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(UdpTransport {
+            socket: self.socket.try_clone()?,
+            remote: self.remote,
+        }))
+    }
+
+    fn is_datagram(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_endpoint() {
+        assert_eq!(Endpoint::parse("COM3").unwrap(), Endpoint::Serial("COM3".to_string()));
+        assert_eq!(
+            Endpoint::parse("/dev/ttyUSB0").unwrap(),
+            Endpoint::Serial("/dev/ttyUSB0".to_string())
+        );
+        assert_eq!(
+            Endpoint::parse("TCP:192.168.0.10:4001").unwrap(),
+            Endpoint::Tcp { host: "192.168.0.10".to_string(), port: 4001 }
+        );
+        assert_eq!(
+            Endpoint::parse("tcp:[::1]:5000").unwrap(),
+            Endpoint::Tcp { host: "::1".to_string(), port: 5000 }
+        );
+        assert_eq!(
+            Endpoint::parse("UDP:LOCALHOST:50000").unwrap(),
+            Endpoint::Udp { host: "LOCALHOST".to_string(), port: 50000, local_port: 0 }
+        );
+        assert_eq!(
+            Endpoint::parse("UDP:10.0.0.5:7000:7001").unwrap(),
+            Endpoint::Udp { host: "10.0.0.5".to_string(), port: 7000, local_port: 7001 }
+        );
+        assert_eq!(
+            Endpoint::parse("UDP:[fe80::1]:7000").unwrap(),
+            Endpoint::Udp { host: "fe80::1".to_string(), port: 7000, local_port: 0 }
+        );
+        assert_eq!(Endpoint::parse("SERVER:10001").unwrap(), Endpoint::TcpServer { port: 10001 });
+        assert_eq!(
+            Endpoint::parse("REPLAY:/tmp/capture_hex.txt").unwrap(),
+            Endpoint::Replay("/tmp/capture_hex.txt".to_string())
+        );
+        assert_eq!(Endpoint::parse("loopback").unwrap(), Endpoint::Loopback);
+        assert!(Endpoint::parse("TCP:localhost").is_err());
+        assert!(Endpoint::parse("TCP:localhost:99999").is_err());
+    }
+
+    /// Read from `transport` until data arrives, for up to two seconds.
+    fn read_data(transport: &mut dyn Transport, buf: &mut [u8]) -> usize {
+        let deadline = std::time::Instant::now() + Duration::from_secs(2);
+        loop {
+            match transport.read(buf) {
+                Ok(n) => return n,
+                Err(e) if e.kind() == io::ErrorKind::TimedOut && std::time::Instant::now() < deadline => {}
+                Err(e) => panic!("read failed: {}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_tcp_server_accepts_next_client_after_disconnect() {
+        let mut server = TcpServerTransport::bind(0).unwrap();
+        let port = server.listener.local_addr().unwrap().port();
+        let mut writer = server.try_clone_transport().unwrap();
+        let mut buf = [0u8; 16];
+
+        assert_eq!(server.read(&mut buf).unwrap_err().kind(), io::ErrorKind::TimedOut);
+        assert_eq!(writer.write(b"x").unwrap_err().kind(), io::ErrorKind::NotConnected);
+
+        let mut first = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        first.write_all(b"one").unwrap();
+        let n = read_data(&mut server, &mut buf);
+        assert_eq!(&buf[..n], b"one");
+        let first_addr = first.local_addr().unwrap();
+        assert_eq!(server.take_peer_event(), Some(PeerEvent::Connected(first_addr)));
+
+        // Clones write to the active client
+        writer.write_all(b"hi").unwrap();
+        first.read_exact(&mut buf[..2]).unwrap();
+        assert_eq!(&buf[..2], b"hi");
+
+        drop(first);
+        let mut second = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        second.write_all(b"two").unwrap();
+        let n = read_data(&mut server, &mut buf);
+        assert_eq!(&buf[..n], b"two");
+        assert_eq!(server.take_peer_event(), Some(PeerEvent::Disconnected(first_addr)));
+        assert_eq!(server.take_peer_event(), Some(PeerEvent::Connected(second.local_addr().unwrap())));
+        assert_eq!(server.take_peer_event(), None);
+
+        writer.write_all(b"hi").unwrap();
+        second.read_exact(&mut buf[..2]).unwrap();
+        assert_eq!(&buf[..2], b"hi");
+    }
+
+    #[test]
+    fn test_udp_only_accepts_the_remote() {
+        let device = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let stranger = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut transport = UdpTransport::open("127.0.0.1", device.local_addr().unwrap().port(), 0).unwrap();
+
+        transport.write_all(b"PING").unwrap();
+        let mut buf = [0u8; 16];
+        let (n, local) = device.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"PING");
+
+        stranger.send_to(b"SPOOF", ("127.0.0.1", local.port())).unwrap();
+        device.send_to(b"PONG", ("127.0.0.1", local.port())).unwrap();
+        assert_eq!(transport.read(&mut buf).unwrap_err().kind(), io::ErrorKind::TimedOut);
+        let n = transport.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"PONG");
+    }
+}
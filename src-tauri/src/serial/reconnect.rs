@@ -0,0 +1,87 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file reconnect.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How a session retries after its port drops. Delays start at
+/// `initial_delay_ms` and double on every failed attempt up to `max_delay_ms`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReconnectPolicy {
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Give up after this many failed attempts; `None` retries until the
+    /// session is closed
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: 500,
+            max_delay_ms: 10_000,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Wait before attempt number `attempt` (0-based), or `None` once the
+    /// policy has run out of attempts.
+    pub fn delay(&self, attempt: u32) -> Option<Duration> {
+        if self.max_attempts.is_some_and(|max| attempt >= max) {
+            return None;
+        }
+        let factor = 1u64.checked_shl(attempt.min(32)).unwrap_or(u64::MAX);
+        let delay = self.initial_delay_ms.saturating_mul(factor).min(self.max_delay_ms);
+        Some(Duration::from_millis(delay))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_backoff() {
+        let policy = ReconnectPolicy { initial_delay_ms: 100, max_delay_ms: 1000, max_attempts: Some(6) };
+        let delays: Vec<u64> = (0..7)
+            .map_while(|attempt| policy.delay(attempt))
+            .map(|d| d.as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+
+        let forever = ReconnectPolicy::default();
+        assert_eq!(forever.delay(1000), Some(Duration::from_millis(10_000)));
+    }
+}
@@ -0,0 +1,106 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file record.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Which way a record's bytes went, seen from the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    #[serde(rename = "RX")]
+    Rx,
+    #[serde(rename = "TX")]
+    Tx,
+}
+
+/// A point in time on both clocks: `monotonic_ns` for measuring intervals
+/// (nanoseconds since the process started, never jumps), `wall_clock` for
+/// display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Timestamp {
+    pub monotonic_ns: u64,
+    pub wall_clock: DateTime<Local>,
+}
+
+fn monotonic_origin() -> Instant {
+    static ORIGIN: OnceLock<Instant> = OnceLock::new();
+    *ORIGIN.get_or_init(Instant::now)
+}
+
+impl Timestamp {
+    pub fn now() -> Self {
+        let origin = monotonic_origin();
+        Self {
+            monotonic_ns: origin.elapsed().as_nanos() as u64,
+            wall_clock: Local::now(),
+        }
+    }
+
+    /// Monotonic time since this timestamp was taken
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(Timestamp::now().monotonic_ns.saturating_sub(self.monotonic_ns))
+    }
+}
+
+/// A line condition recorded at its place in the data stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Marker {
+    BreakOn,
+    BreakOff,
+}
+
+impl Marker {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Marker::BreakOn => "BREAK on",
+            Marker::BreakOff => "BREAK off",
+        }
+    }
+}
+
+/// One frame received or sent on a session. `seq` is shared by both
+/// directions, so sorting by it gives the order the session saw the traffic.
+/// Marker records carry no data.
+#[derive(Debug, Clone, Serialize)]
+pub struct Record {
+    pub session_id: String,
+    pub seq: u64,
+    pub direction: Direction,
+    pub first_byte: Timestamp,
+    pub last_byte: Timestamp,
+    pub data: Vec<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marker: Option<Marker>,
+}
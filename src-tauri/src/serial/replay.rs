@@ -0,0 +1,384 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file replay.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use crate::commands::log::{parse_line, LogFormat};
+use crate::commands::pcap::read_pcapng;
+use crate::serial::port::{Transport, READ_TIMEOUT};
+use crate::serial::record::Direction;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// A received frame from a capture, `offset` after the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayFrame {
+    pub offset: Duration,
+    pub data: Vec<u8>,
+}
+
+/// Load the RX frames of a pcapng capture or a termLight log file (any of
+/// the four formats, optionally gzipped). Sent frames are left out: during
+/// a replay those come from the application.
+pub fn load_replay(path: &Path) -> Result<Vec<ReplayFrame>, String> {
+    let mut bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if bytes.starts_with(&[0x1F, 0x8B]) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(bytes.as_slice())
+            .read_to_end(&mut decompressed)
+            .map_err(|e| format!("Failed to decompress {}: {}", path.display(), e))?;
+        bytes = decompressed;
+    }
+
+    // (nanoseconds, data) in capture order
+    let received: Vec<(i128, Vec<u8>)> = if bytes.starts_with(&[0x0A, 0x0D, 0x0D, 0x0A]) {
+        read_pcapng(&bytes)?
+            .into_iter()
+            .filter(|packet| packet.direction != Some(Direction::Tx))
+            .map(|packet| (packet.timestamp_ns as i128, packet.data))
+            .collect()
+    } else {
+        let format = LogFormat::from_path(path).ok_or_else(|| {
+            format!("Can't tell the log format of {} (expected a _asc, _hex, _dec or _bin file)", path.display())
+        })?;
+        String::from_utf8_lossy(&bytes)
+            .lines()
+            .filter_map(|line| parse_line(format, line))
+            .filter(|frame| frame.direction == Direction::Rx)
+            .filter_map(|frame| {
+                let nanos = frame.time.and_utc().timestamp_nanos_opt()?;
+                Some((nanos as i128, frame.data))
+            })
+            .collect()
+    };
+
+    let start = received.first().map_or(0, |(nanos, _)| *nanos);
+    let mut frames: Vec<ReplayFrame> = received
+        .into_iter()
+        .map(|(nanos, data)| ReplayFrame {
+            offset: Duration::from_nanos((nanos - start).max(0) as u64),
+            data,
+        })
+        .collect();
+    frames.sort_by_key(|frame| frame.offset);
+    Ok(frames)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ReplayMode {
+    /// Original timing divided by `speed` (2.0 plays twice as fast)
+    Timed { speed: f64 },
+    /// Only play frames released with `ReplayControl::step`
+    Step,
+}
+
+impl Default for ReplayMode {
+    fn default() -> Self {
+        ReplayMode::Timed { speed: 1.0 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStatus {
+    /// Frames played so far
+    pub position: usize,
+    pub total: usize,
+    #[serde(flatten)]
+    pub mode: ReplayMode,
+    pub finished: bool,
+}
+
+/// Playback state shared by a replay session's handles and the commands
+/// that steer it.
+pub struct ReplayControl {
+    state: Mutex<ReplayState>,
+    changed: Condvar,
+}
+
+struct ReplayState {
+    frames: Vec<ReplayFrame>,
+    next: usize,
+    mode: ReplayMode,
+    /// When offset zero is (or would have been) due at the current speed
+    origin: Instant,
+    steps: u64,
+}
+
+impl ReplayState {
+    /// Start timing from the last frame played, so the gap to the next one
+    /// is kept after a pause or speed change.
+    fn rebase(&mut self) {
+        if let ReplayMode::Timed { speed } = self.mode {
+            let played = self.next.checked_sub(1).map_or(Duration::ZERO, |i| self.frames[i].offset);
+            self.origin = Instant::now() - played.div_f64(speed);
+        }
+    }
+
+    fn due(&self) -> Option<Instant> {
+        let frame = self.frames.get(self.next)?;
+        match self.mode {
+            ReplayMode::Timed { speed } => Some(self.origin + frame.offset.div_f64(speed)),
+            ReplayMode::Step => None,
+        }
+    }
+}
+
+impl ReplayControl {
+    fn new(frames: Vec<ReplayFrame>) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(ReplayState {
+                frames,
+                next: 0,
+                mode: ReplayMode::default(),
+                origin: Instant::now(),
+                steps: 0,
+            }),
+            changed: Condvar::new(),
+        })
+    }
+
+    pub fn set_mode(&self, mode: ReplayMode) -> Result<(), String> {
+        if let ReplayMode::Timed { speed } = mode {
+            if !(speed.is_finite() && speed > 0.0) {
+                return Err(format!("Invalid replay speed: {}", speed));
+            }
+        }
+        let mut state = self.lock()?;
+        state.mode = mode;
+        state.steps = 0;
+        state.rebase();
+        self.changed.notify_all();
+        Ok(())
+    }
+
+    /// Release the next `count` frames in step mode.
+    pub fn step(&self, count: u64) -> Result<(), String> {
+        let mut state = self.lock()?;
+        if state.mode != ReplayMode::Step {
+            return Err("Replay is not in step mode".to_string());
+        }
+        state.steps += count;
+        self.changed.notify_all();
+        Ok(())
+    }
+
+    /// Play again from the first frame.
+    pub fn restart(&self) -> Result<(), String> {
+        let mut state = self.lock()?;
+        state.next = 0;
+        state.steps = 0;
+        state.origin = Instant::now();
+        self.changed.notify_all();
+        Ok(())
+    }
+
+    pub fn status(&self) -> Result<ReplayStatus, String> {
+        let state = self.lock()?;
+        Ok(ReplayStatus {
+            position: state.next,
+            total: state.frames.len(),
+            mode: state.mode,
+            finished: state.next >= state.frames.len(),
+        })
+    }
+
+    /// Wait up to `timeout` for the next frame to come due.
+    fn next_frame(&self, timeout: Duration) -> io::Result<Vec<u8>> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.lock().map_err(io::Error::other)?;
+        loop {
+            let ready = match state.mode {
+                ReplayMode::Timed { .. } => state.due().is_some_and(|due| due <= Instant::now()),
+                ReplayMode::Step => state.steps > 0 && state.next < state.frames.len(),
+            };
+            if ready {
+                let frame = state.frames[state.next].data.clone();
+                state.next += 1;
+                if state.mode == ReplayMode::Step {
+                    state.steps -= 1;
+                }
+                return Ok(frame);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(io::Error::from(io::ErrorKind::TimedOut));
+            }
+            let wake = state.due().map_or(deadline, |due| due.min(deadline));
+            state = self
+                .changed
+                .wait_timeout(state, wake.saturating_duration_since(now))
+                .map_err(|_| io::Error::other("Failed to lock replay mutex"))?
+                .0;
+        }
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, ReplayState>, String> {
+        self.state.lock().map_err(|_| "Failed to lock replay mutex".to_string())
+    }
+}
+
+/// Plays a recorded capture back as if it were a port. Received frames come
+/// out of `read` on their original schedule, one frame per read; anything
+/// written is accepted and only shows up in the session's TX records.
+pub struct ReplayTransport {
+    control: Arc<ReplayControl>,
+    timeout: Duration,
+    /// Rest of a frame larger than the caller's buffer
+    pending: Vec<u8>,
+}
+
+impl ReplayTransport {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let frames = load_replay(path)?;
+        println!("Replaying {} frames from {}", frames.len(), path.display());
+        Ok(Self {
+            control: ReplayControl::new(frames),
+            timeout: READ_TIMEOUT,
+            pending: Vec::new(),
+        })
+    }
+}
+
+impl Read for ReplayTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            self.pending = self.control.next_frame(self.timeout)?;
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+impl Write for ReplayTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(ReplayTransport {
+            control: self.control.clone(),
+            timeout: self.timeout,
+            pending: Vec::new(),
+        }))
+    }
+
+    /// Frames are replayed exactly as they were captured
+    fn is_datagram(&self) -> bool {
+        true
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    fn replay_control(&self) -> Option<Arc<ReplayControl>> {
+        Some(self.control.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_timing_and_steps() {
+        let frames = vec![
+            ReplayFrame { offset: Duration::ZERO, data: b"one".to_vec() },
+            ReplayFrame { offset: Duration::from_millis(400), data: b"two".to_vec() },
+            ReplayFrame { offset: Duration::from_millis(800), data: b"three".to_vec() },
+        ];
+        let mut port = ReplayTransport {
+            control: ReplayControl::new(frames),
+            timeout: Duration::from_millis(50),
+            pending: Vec::new(),
+        };
+        let mut buf = [0u8; 16];
+
+        // Four times as fast: "two" is due 100 ms in
+        port.control.set_mode(ReplayMode::Timed { speed: 4.0 }).unwrap();
+        assert_eq!(port.read(&mut buf).unwrap(), 3);
+        assert_eq!(port.read(&mut buf).unwrap_err().kind(), io::ErrorKind::TimedOut);
+        port.set_read_timeout(Duration::from_millis(200)).unwrap();
+        let started = Instant::now();
+        let n = port.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"two");
+        assert!(started.elapsed() < Duration::from_millis(150));
+
+        port.control.set_mode(ReplayMode::Step).unwrap();
+        assert_eq!(port.read(&mut buf).unwrap_err().kind(), io::ErrorKind::TimedOut);
+        port.control.step(1).unwrap();
+        let mut small = [0u8; 2];
+        assert_eq!(port.read(&mut small).unwrap(), 2);
+        assert_eq!(port.read(&mut buf).unwrap(), 3);
+
+        let status = port.control.status().unwrap();
+        assert!(status.finished);
+        assert_eq!(status.total, 3);
+    }
+
+    #[test]
+    fn test_load_replay_from_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bench_COM3_hex.txt");
+        std::fs::write(
+            &path,
+            "18/10/2026 14:23:01.100 Log started\r\n\
+             18/10/2026 14:23:01.250 [TX] - 41 54 0D\r\n\
+             18/10/2026 14:23:01.500 [RX] - 4F 4B\r\n\
+             18/10/2026 14:23:02.750 [RX] - 0D 0A\r\n",
+        )
+        .unwrap();
+
+        let frames = load_replay(&path).unwrap();
+        assert_eq!(
+            frames,
+            vec![
+                ReplayFrame { offset: Duration::ZERO, data: b"OK".to_vec() },
+                ReplayFrame { offset: Duration::from_millis(1250), data: b"\r\n".to_vec() },
+            ]
+        );
+        assert!(load_replay(&dir.path().join("bench.txt")).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file sequence.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use std::time::Duration;
+
+/// Break length used by a bare `BRK` step; long enough for bootloaders that
+/// sample the line at low baud rates.
+pub const DEFAULT_BREAK: Duration = Duration::from_millis(250);
+
+/// One action of a stored send sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendStep {
+    Data(Vec<u8>),
+    /// Hold the line in the break condition for the given time
+    Break(Duration),
+}
+
+/// Parse a send sequence: space-separated hex bytes as stored in a
+/// project's SEND entries, optionally mixed with `BRK` or `BRK:<ms>` steps.
+/// Consecutive bytes are merged into a single `Data` step.
+pub fn parse_sequence(sequence: &str) -> Result<Vec<SendStep>, String> {
+    let mut steps = Vec::new();
+    let mut data = Vec::new();
+
+    for token in sequence.split_whitespace() {
+        let upper = token.to_ascii_uppercase();
+        if let Some(rest) = upper.strip_prefix("BRK") {
+            let duration = match rest.strip_prefix(':') {
+                Some(ms) => Duration::from_millis(
+                    ms.parse().map_err(|_| format!("Invalid break duration in '{}'", token))?,
+                ),
+                None if rest.is_empty() => DEFAULT_BREAK,
+                None => return Err(format!("Invalid sequence token '{}'", token)),
+            };
+            if !data.is_empty() {
+                steps.push(SendStep::Data(std::mem::take(&mut data)));
+            }
+            steps.push(SendStep::Break(duration));
+        } else if token.len() == 2 {
+            let byte = u8::from_str_radix(token, 16)
+                .map_err(|_| format!("Invalid hex byte '{}'", token))?;
+            data.push(byte);
+        } else {
+            return Err(format!("Invalid sequence token '{}'", token));
+        }
+    }
+
+    if !data.is_empty() {
+        steps.push(SendStep::Data(data));
+    }
+
+    Ok(steps)
+}
+
+/// One position of a receive sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiveByte {
+    Byte(u8),
+    /// Docklight's `?`: exactly one byte, whatever its value
+    Any,
+    /// Docklight's `#`: one byte of any value, or none
+    Optional,
+}
+
+/// Parse a receive sequence: space-separated hex bytes as stored in a
+/// project's RECEIVE entries, with Docklight's `?` and `#` wildcards
+/// (written `??`/`##` in hex views, accepted either way).
+pub fn parse_receive_sequence(sequence: &str) -> Result<Vec<ReceiveByte>, String> {
+    let pattern = sequence
+        .split_whitespace()
+        .map(|token| match token {
+            "?" | "??" => Ok(ReceiveByte::Any),
+            "#" | "##" => Ok(ReceiveByte::Optional),
+            _ if token.len() == 2 => u8::from_str_radix(token, 16)
+                .map(ReceiveByte::Byte)
+                .map_err(|_| format!("Invalid hex byte '{}'", token)),
+            _ => Err(format!("Invalid receive sequence token '{}'", token)),
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    if !pattern.is_empty() && pattern.iter().all(|b| *b == ReceiveByte::Optional) {
+        return Err("Receive sequence must contain more than '#' wildcards".to_string());
+    }
+    Ok(pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sequence() {
+        assert_eq!(
+            parse_sequence("2D 2D 6F").unwrap(),
+            vec![SendStep::Data(vec![0x2D, 0x2D, 0x6F])]
+        );
+        assert_eq!(
+            parse_sequence("BRK 7F 7F brk:50 0D").unwrap(),
+            vec![
+                SendStep::Break(DEFAULT_BREAK),
+                SendStep::Data(vec![0x7F, 0x7F]),
+                SendStep::Break(Duration::from_millis(50)),
+                SendStep::Data(vec![0x0D]),
+            ]
+        );
+        assert!(parse_sequence("2D ZZ").is_err());
+        assert!(parse_sequence("BRK:abc").is_err());
+        assert!(parse_sequence("BRKX").is_err());
+
+        assert_eq!(
+            parse_receive_sequence("4f ?? 0D # ? ##").unwrap(),
+            vec![
+                ReceiveByte::Byte(0x4F),
+                ReceiveByte::Any,
+                ReceiveByte::Byte(0x0D),
+                ReceiveByte::Optional,
+                ReceiveByte::Any,
+                ReceiveByte::Optional,
+            ]
+        );
+        assert!(parse_receive_sequence("4F BRK").is_err());
+        assert!(parse_receive_sequence("# ##").is_err());
+    }
+}
@@ -0,0 +1,334 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file simulator.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use crate::commands::file::ProjectData;
+use crate::serial::matcher::{Automaton, ScanState};
+use crate::serial::sequence::{parse_receive_sequence, parse_sequence, SendStep};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Counters and state of one rule, for `get_simulator_status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleStatus {
+    pub receive_index: i32,
+    pub send_index: i32,
+    pub enabled: bool,
+    pub matches: u64,
+    pub answers: u64,
+}
+
+/// Plays the device side of a project: received data is scanned for the
+/// receive sequences of its simulator rules, also across reads, and each
+/// match queues the rule's send sequence. Answers go out in order of when they are due on a
+/// worker thread of their own, so delays never hold up the read thread.
+pub struct Simulator {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    rules: Vec<Rule>,
+    /// Pattern ids are rule indices
+    automaton: Automaton,
+    scan: Mutex<ScanState>,
+    queue: Mutex<Queue>,
+    wake: Condvar,
+}
+
+struct Rule {
+    receive_index: i32,
+    send_index: i32,
+    answer: Vec<SendStep>,
+    delay: Duration,
+    max_answers: u64,
+    enabled: AtomicBool,
+    matches: AtomicU64,
+    answers: AtomicU64,
+}
+
+#[derive(Default)]
+struct Queue {
+    /// Rule index of each pending answer, keyed by due time and arrival
+    pending: BTreeMap<(Instant, u64), usize>,
+    next_id: u64,
+    stopped: bool,
+}
+
+impl Simulator {
+    /// Compile the project's simulator rules and start answering through
+    /// `send`. Fails if a rule points at a missing or unparsable sequence.
+    pub fn start(
+        project: &ProjectData,
+        send: impl FnMut(&[SendStep]) -> Result<(), String> + Send + 'static,
+    ) -> Result<Arc<Self>, String> {
+        let rules = project
+            .simulator_rules
+            .iter()
+            .map(|rule| {
+                let receive = project
+                    .receive_commands
+                    .iter()
+                    .find(|cmd| cmd.index == rule.receive_index)
+                    .ok_or_else(|| format!("Simulator rule refers to missing receive sequence {}", rule.receive_index))?;
+                let send = project
+                    .send_commands
+                    .iter()
+                    .find(|cmd| cmd.index == rule.send_index)
+                    .ok_or_else(|| format!("Simulator rule refers to missing send sequence {}", rule.send_index))?;
+
+                let pattern = parse_receive_sequence(&receive.hex_data)
+                    .map_err(|e| format!("Receive sequence '{}': {}", receive.name, e))?;
+                if pattern.is_empty() {
+                    return Err(format!("Receive sequence '{}' is empty", receive.name));
+                }
+                Ok((pattern, Rule {
+                    receive_index: rule.receive_index,
+                    send_index: rule.send_index,
+                    answer: parse_sequence(&send.hex_data)
+                        .map_err(|e| format!("Send sequence '{}': {}", send.name, e))?,
+                    delay: Duration::from_millis(rule.delay_ms),
+                    max_answers: rule.max_answers,
+                    enabled: AtomicBool::new(rule.enabled),
+                    matches: AtomicU64::new(0),
+                    answers: AtomicU64::new(0),
+                }))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let (patterns, rules): (Vec<_>, Vec<_>) = rules.into_iter().unzip();
+
+        let shared = Arc::new(Shared {
+            rules,
+            automaton: Automaton::new(&patterns)?,
+            scan: Mutex::new(ScanState::default()),
+            queue: Mutex::new(Queue::default()),
+            wake: Condvar::new(),
+        });
+        spawn_answer_thread(shared.clone(), send);
+        Ok(Arc::new(Self { shared }))
+    }
+
+    /// Scan received data and queue an answer for each occurrence of an
+    /// enabled rule's receive sequence.
+    pub fn on_receive(&self, data: &[u8]) {
+        let mut matched = Vec::new();
+        match self.shared.scan.lock() {
+            Ok(mut scan) => self.shared.automaton.scan(&mut scan, data, |found| matched.push(found.pattern)),
+            Err(_) => return,
+        }
+
+        let now = Instant::now();
+        let Ok(mut queue) = self.shared.queue.lock() else {
+            return;
+        };
+        for index in matched {
+            let rule = &self.shared.rules[index];
+            if !rule.enabled.load(Ordering::SeqCst) {
+                continue;
+            }
+            rule.matches.fetch_add(1, Ordering::SeqCst);
+            let answered = rule.answers.load(Ordering::SeqCst);
+            if rule.max_answers != 0 && answered >= rule.max_answers {
+                continue;
+            }
+            rule.answers.fetch_add(1, Ordering::SeqCst);
+            let id = queue.next_id;
+            queue.next_id += 1;
+            queue.pending.insert((now + rule.delay, id), index);
+        }
+        self.shared.wake.notify_all();
+    }
+
+    /// Turn rule `rule` (its position in the project's simulator rules) on
+    /// or off. Answers already queued still go out.
+    pub fn set_enabled(&self, rule: usize, enabled: bool) -> Result<(), String> {
+        self.shared
+            .rules
+            .get(rule)
+            .ok_or_else(|| format!("No simulator rule {}", rule))?
+            .enabled
+            .store(enabled, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Zero the counters, which also lets rules with `max_answers` answer again.
+    pub fn reset_counters(&self) {
+        for rule in &self.shared.rules {
+            rule.matches.store(0, Ordering::SeqCst);
+            rule.answers.store(0, Ordering::SeqCst);
+        }
+    }
+
+    pub fn status(&self) -> Vec<RuleStatus> {
+        self.shared
+            .rules
+            .iter()
+            .map(|rule| RuleStatus {
+                receive_index: rule.receive_index,
+                send_index: rule.send_index,
+                enabled: rule.enabled.load(Ordering::SeqCst),
+                matches: rule.matches.load(Ordering::SeqCst),
+                answers: rule.answers.load(Ordering::SeqCst),
+            })
+            .collect()
+    }
+
+    /// Stop the answer thread, dropping answers that are still pending.
+    pub fn stop(&self) {
+        if let Ok(mut queue) = self.shared.queue.lock() {
+            queue.stopped = true;
+            queue.pending.clear();
+        }
+        self.shared.wake.notify_all();
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn spawn_answer_thread(shared: Arc<Shared>, mut send: impl FnMut(&[SendStep]) -> Result<(), String> + Send + 'static) {
+    std::thread::spawn(move || loop {
+        let Ok(mut queue) = shared.queue.lock() else {
+            return;
+        };
+        let rule = loop {
+            if queue.stopped {
+                return;
+            }
+            let now = Instant::now();
+            match queue.pending.first_key_value() {
+                Some((&(due, _), _)) if due <= now => break queue.pending.pop_first().map(|(_, rule)| rule),
+                Some((&(due, _), _)) => {
+                    queue = match shared.wake.wait_timeout(queue, due - now) {
+                        Ok((queue, _)) => queue,
+                        Err(_) => return,
+                    };
+                }
+                None => {
+                    queue = match shared.wake.wait(queue) {
+                        Ok(queue) => queue,
+                        Err(_) => return,
+                    };
+                }
+            }
+        };
+        drop(queue);
+
+        if let Some(rule) = rule.and_then(|index| shared.rules.get(index)) {
+            if let Err(e) = send(&rule.answer) {
+                println!("Simulator failed to answer with send sequence {}: {}", rule.send_index, e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::file::{CommSettings, ReceiveCommand, SendCommand, SimulatorRule};
+    use std::sync::mpsc;
+
+    fn project(rules: Vec<SimulatorRule>) -> ProjectData {
+        let send = |index: i32, hex_data: &str| SendCommand {
+            index,
+            name: format!("Answer {}", index),
+            hex_data: hex_data.to_string(),
+            repetition_mode: 0,
+            color_index: 5,
+        };
+        let receive = |index: i32, hex_data: &str| ReceiveCommand {
+            index,
+            name: format!("Request {}", index),
+            hex_data: hex_data.to_string(),
+            param1: 0,
+            param2: 0,
+            comment: String::new(),
+            comment_text: String::new(),
+            param3: 0,
+            param4: 0,
+            param5: 0,
+            param6: 0,
+        };
+        ProjectData {
+            version: 8,
+            comm_settings: CommSettings { params: vec![] },
+            comm_display: 0,
+            comm_channels: vec![],
+            send_commands: vec![send(0, "4F 4B"), send(1, "53 4C 4F 57")],
+            receive_commands: vec![receive(0, "41 54"), receive(1, "3F")],
+            versatap: None,
+            channel_alias: vec![],
+            simulator_rules: rules,
+        }
+    }
+
+    #[test]
+    fn test_simulator_answers() {
+        let rule = |receive_index, send_index, delay_ms, max_answers| SimulatorRule {
+            receive_index,
+            send_index,
+            delay_ms,
+            enabled: true,
+            max_answers,
+        };
+        let (tx, rx) = mpsc::channel();
+        let simulator = Simulator::start(&project(vec![rule(1, 1, 100, 0), rule(0, 0, 0, 2)]), move |steps| {
+            tx.send(steps.to_vec()).map_err(|e| e.to_string())
+        })
+        .unwrap();
+        let data = |bytes: &[u8]| vec![SendStep::Data(bytes.to_vec())];
+
+        // The delayed answer to "?" goes out after the immediate one to "AT"
+        simulator.on_receive(b"?AT\r\nA");
+        simulator.on_receive(b"T\r\nAT\r\n");
+        let timeout = Duration::from_secs(1);
+        assert_eq!(rx.recv_timeout(timeout).unwrap(), data(b"OK"));
+        assert_eq!(rx.recv_timeout(timeout).unwrap(), data(b"OK"));
+        assert_eq!(rx.recv_timeout(timeout).unwrap(), data(b"SLOW"));
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+        assert_eq!(simulator.status()[1], RuleStatus { receive_index: 0, send_index: 0, enabled: true, matches: 3, answers: 2 });
+
+        simulator.set_enabled(0, false).unwrap();
+        simulator.reset_counters();
+        simulator.on_receive(b"?AT");
+        assert_eq!(rx.recv_timeout(timeout).unwrap(), data(b"OK"));
+        assert!(rx.recv_timeout(Duration::from_millis(150)).is_err());
+        assert!(simulator.set_enabled(2, true).is_err());
+
+        let missing = project(vec![rule(0, 7, 0, 0)]);
+        assert!(Simulator::start(&missing, |_| Ok(())).is_err());
+    }
+}
@@ -29,23 +29,225 @@
  * @date 2025-11-19
  */
 
-use serialport::SerialPort;
+use crate::commands::log::SessionLog;
+use crate::commands::pcap::PcapCapture;
+use crate::serial::history::{History, HistoryLimits};
+use crate::serial::manager::SessionManager;
+use crate::serial::matcher::ReceiveMatcher;
+use crate::serial::port::Transport;
+use crate::serial::simulator::Simulator;
+use crate::serial::stream::RecordStream;
+use chrono::{Local, SecondsFormat};
+use serde::Serialize;
 use std::sync::Mutex;
 use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tauri::{AppHandle, Emitter, Runtime};
 
 pub struct SerialState {
-    pub port: Mutex<Option<Box<dyn SerialPort>>>,
-    pub is_connected: AtomicBool,
-    pub should_stop: Arc<AtomicBool>,
+    pub sessions: SessionManager,
 }
 
 impl SerialState {
     pub fn new() -> Self {
         Self {
-            port: Mutex::new(None),
-            is_connected: AtomicBool::new(false),
+            sessions: SessionManager::new(),
+        }
+    }
+}
+
+/// Lifecycle of a session as reported by `connection-state` events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum ConnectionState {
+    Opening,
+    Open,
+    /// The port failed; `kind` is the I/O error kind, e.g. "BrokenPipe"
+    Error { message: String, kind: String },
+    /// Waiting to reopen the port after an error
+    Reconnecting,
+    Closed,
+}
+
+#[derive(Clone, Serialize)]
+struct ConnectionStatePayload {
+    session_id: String,
+    port_name: String,
+    #[serde(flatten)]
+    state: ConnectionState,
+    timestamp: String,
+}
+
+/// Emit a `connection-state` event. Used directly for sessions that don't
+/// exist yet (opening, or failing to open); afterwards go through
+/// `Session::set_connection_state` so the state is also remembered.
+pub fn emit_connection_state<R: Runtime>(app: &AppHandle<R>, session_id: &str, port_name: &str, state: ConnectionState) {
+    println!("Session '{}' on {}: {:?}", session_id, port_name, state);
+    let payload = ConnectionStatePayload {
+        session_id: session_id.to_string(),
+        port_name: port_name.to_string(),
+        state,
+        timestamp: Local::now().to_rfc3339_opts(SecondsFormat::Micros, false),
+    };
+    if let Err(e) = app.emit("connection-state", payload) {
+        eprintln!("Failed to emit connection-state event: {}", e);
+    }
+}
+
+/// One open channel. The read thread owns its own clone of the transport;
+/// `port` is the handle used for writing, absent for monitoring sessions.
+pub struct Session {
+    pub session_id: String,
+    pub port_name: String,
+    pub port: Mutex<Option<Box<dyn Transport>>>,
+    pub is_connected: AtomicBool,
+    pub should_stop: Arc<AtomicBool>,
+    pub connection_state: Mutex<ConnectionState>,
+    /// Packet events that couldn't be delivered to the frontend
+    pub emit_failures: AtomicU64,
+    next_seq: AtomicU64,
+    /// Recent traffic in both directions, for scrollback after a reload
+    pub history: Mutex<History>,
+    /// Binary channel to the frontend; without one, records go out as events
+    stream: Mutex<Option<Arc<RecordStream>>>,
+    log: Mutex<Option<SessionLog>>,
+    capture: Mutex<Option<Arc<PcapCapture>>>,
+    /// Answers received data when termLight plays the device
+    simulator: Mutex<Option<Arc<Simulator>>>,
+    /// Receive sequences to report as `receive-match` events
+    matcher: Mutex<Option<ReceiveMatcher>>,
+    /// Device paths the session has open right now: both sides of a tap or
+    /// bridge, and wherever a reconnect found the device
+    devices: Mutex<Vec<String>>,
+}
+
+impl Session {
+    pub fn new(session_id: &str, port_name: &str, port: Option<Box<dyn Transport>>) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            port_name: port_name.to_string(),
+            port: Mutex::new(port),
+            is_connected: AtomicBool::new(true),
             should_stop: Arc::new(AtomicBool::new(false)),
+            connection_state: Mutex::new(ConnectionState::Open),
+            emit_failures: AtomicU64::new(0),
+            next_seq: AtomicU64::new(0),
+            history: Mutex::new(History::new(HistoryLimits::default())),
+            stream: Mutex::new(None),
+            log: Mutex::new(None),
+            capture: Mutex::new(None),
+            simulator: Mutex::new(None),
+            matcher: Mutex::new(None),
+            devices: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record and announce a state change, keeping `is_connected` in step.
+    pub fn set_connection_state<R: Runtime>(&self, app: &AppHandle<R>, state: ConnectionState) {
+        self.is_connected.store(state == ConnectionState::Open, Ordering::SeqCst);
+        if let Ok(mut current) = self.connection_state.lock() {
+            *current = state.clone();
+        }
+        emit_connection_state(app, &self.session_id, &self.port_name, state);
+    }
+
+    /// Sequence number for the next RX or TX record of this session
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq.fetch_add(1, Ordering::SeqCst)
+    }
+
+    pub fn stream(&self) -> Option<Arc<RecordStream>> {
+        self.stream.lock().ok()?.clone()
+    }
+
+    /// Route records to `stream` from now on (or back to events with `None`),
+    /// closing the stream it replaces.
+    pub fn set_stream(&self, stream: Option<Arc<RecordStream>>) {
+        if let Ok(mut current) = self.stream.lock() {
+            if let Some(old) = std::mem::replace(&mut *current, stream) {
+                old.close();
+            }
         }
     }
+
+    pub fn lock_history(&self) -> Result<std::sync::MutexGuard<'_, History>, String> {
+        self.history.lock().map_err(|_| "Failed to lock history mutex".to_string())
+    }
+
+    pub fn lock_log(&self) -> Result<std::sync::MutexGuard<'_, Option<SessionLog>>, String> {
+        self.log.lock().map_err(|_| "Failed to lock log mutex".to_string())
+    }
+
+    /// Start writing to `log`, or stop logging with `None`. The log it
+    /// replaces is flushed and closed before this returns.
+    pub fn set_log(&self, log: Option<SessionLog>) {
+        let old = match self.log.lock() {
+            Ok(mut current) => std::mem::replace(&mut *current, log),
+            Err(_) => return,
+        };
+        if let Some(old) = old {
+            old.stop();
+        }
+    }
+
+    pub fn devices(&self) -> Vec<String> {
+        self.devices.lock().map(|devices| devices.clone()).unwrap_or_default()
+    }
+
+    pub fn set_devices(&self, devices: Vec<String>) {
+        if let Ok(mut current) = self.devices.lock() {
+            *current = devices;
+        }
+    }
+
+    pub fn capture(&self) -> Option<Arc<PcapCapture>> {
+        self.capture.lock().ok()?.clone()
+    }
+
+    pub fn set_capture(&self, capture: Option<Arc<PcapCapture>>) {
+        if let Ok(mut current) = self.capture.lock() {
+            if let Some(old) = std::mem::replace(&mut *current, capture) {
+                println!("Session '{}' stopped capturing to {}", self.session_id, old.path());
+            }
+        }
+    }
+
+    pub fn lock_matcher(&self) -> Result<std::sync::MutexGuard<'_, Option<ReceiveMatcher>>, String> {
+        self.matcher.lock().map_err(|_| "Failed to lock matcher mutex".to_string())
+    }
+
+    pub fn simulator(&self) -> Option<Arc<Simulator>> {
+        self.simulator.lock().ok()?.clone()
+    }
+
+    /// Replace the session's simulator, stopping the old one.
+    pub fn set_simulator(&self, simulator: Option<Arc<Simulator>>) {
+        let old = match self.simulator.lock() {
+            Ok(mut current) => std::mem::replace(&mut *current, simulator),
+            Err(_) => return,
+        };
+        if let Some(old) = old {
+            old.stop();
+            println!("Session '{}' stopped simulating", self.session_id);
+        }
+    }
+
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection_state
+            .lock()
+            .map(|state| state.clone())
+            .unwrap_or(ConnectionState::Closed)
+    }
+
+    /// Write `data` to the session's send channel and flush it.
+    pub fn write(&self, data: &[u8]) -> Result<(), String> {
+        let mut port_lock = self.port.lock().map_err(|_| "Failed to lock port mutex")?;
+        let port = port_lock
+            .as_mut()
+            .ok_or_else(|| format!("Session on {} has no channel to send on", self.port_name))?;
+
+        port.write_all(data)
+            .map_err(|e| format!("Failed to write to port: {}", e))?;
+        port.flush().map_err(|e| format!("Failed to flush port: {}", e))
+    }
 }
@@ -0,0 +1,332 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file stream.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use crate::serial::record::{Direction, Marker, Record, Timestamp};
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tauri::ipc::{Channel, InvokeResponseBody};
+
+/// Data bytes a stream holds for a slow frontend before it starts dropping
+pub const DEFAULT_BUFFER_BYTES: usize = 4 * 1024 * 1024;
+
+/// Batches the frontend may have unacknowledged before sending pauses
+const MAX_IN_FLIGHT: u64 = 4;
+
+/// How long the oldest unacknowledged batch may go without an ack before
+/// the frontend is taken to be gone (e.g. reloaded without detaching)
+const ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Soft cap on one batch; a single larger record still goes out alone
+const MAX_BATCH_BYTES: usize = 256 * 1024;
+
+const BATCH_HEADER_LEN: usize = 28;
+const RECORD_HEADER_LEN: usize = 47;
+
+/// What became of a record handed to `RecordStream::push`.
+#[derive(Debug)]
+pub enum Push {
+    Queued,
+    /// The buffer was full; the record was dropped and counted
+    Dropped,
+    /// The stream is closed (the frontend went away or stopped acking); the
+    /// record is handed back so it can be delivered some other way
+    Closed(Record),
+}
+
+/// Delivers a session's records to the frontend over an IPC channel as
+/// binary batches instead of one JSON event per frame.
+///
+/// Records wait in a buffer bounded to `capacity` data bytes. A sender thread
+/// drains it while the frontend has fewer than `MAX_IN_FLIGHT` batches
+/// unacknowledged; when the frontend falls behind, the buffer fills and new
+/// records are dropped and counted instead of piling up in memory. A
+/// frontend that stops acknowledging altogether (a reload that never
+/// detached) gets the stream closed after `ACK_TIMEOUT`.
+pub struct RecordStream {
+    queue: Mutex<StreamQueue>,
+    changed: Condvar,
+}
+
+struct StreamQueue {
+    records: VecDeque<(Record, u8)>,
+    queued_bytes: usize,
+    capacity: usize,
+    /// Dropped since the last batch went out
+    dropped_bytes: u64,
+    dropped_records: u64,
+    /// Dropped over the life of the stream
+    total_dropped_bytes: u64,
+    next_batch: u64,
+    acked_batches: u64,
+    /// When each unacknowledged batch was sent, oldest first
+    sent_at: VecDeque<Instant>,
+    ack_timeout: Duration,
+    closed: bool,
+}
+
+impl RecordStream {
+    pub fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            queue: Mutex::new(StreamQueue {
+                records: VecDeque::new(),
+                queued_bytes: 0,
+                capacity,
+                dropped_bytes: 0,
+                dropped_records: 0,
+                total_dropped_bytes: 0,
+                next_batch: 0,
+                acked_batches: 0,
+                sent_at: VecDeque::new(),
+                ack_timeout: ACK_TIMEOUT,
+                closed: false,
+            }),
+            changed: Condvar::new(),
+        })
+    }
+
+    /// Queue `record` (from side `channel` of a two-port session, else 0).
+    pub fn push(&self, record: Record, channel: u8) -> Push {
+        let Ok(mut queue) = self.lock() else {
+            return Push::Closed(record);
+        };
+        let len = record.data.len();
+        if !queue.closed && queue.sent_at.front().is_some_and(|sent| sent.elapsed() > queue.ack_timeout) {
+            println!("Stream batches unacknowledged for {:?}, closing the stream", queue.ack_timeout);
+            queue.closed = true;
+            self.changed.notify_all();
+        }
+        if queue.closed {
+            return Push::Closed(record);
+        }
+        // An empty buffer always takes one record, however large
+        if !queue.records.is_empty() && queue.queued_bytes + len > queue.capacity {
+            queue.dropped_bytes += len as u64;
+            queue.dropped_records += 1;
+            queue.total_dropped_bytes += len as u64;
+            return Push::Dropped;
+        }
+        queue.queued_bytes += len;
+        queue.records.push_back((record, channel));
+        self.changed.notify_all();
+        Push::Queued
+    }
+
+    /// The frontend has processed every batch up to and including `batch_id`.
+    pub fn ack(&self, batch_id: u64) {
+        if let Ok(mut queue) = self.lock() {
+            let acked = queue.acked_batches.max(batch_id + 1).min(queue.next_batch);
+            let newly_acked = (acked - queue.acked_batches) as usize;
+            queue.sent_at.drain(..newly_acked);
+            queue.acked_batches = acked;
+            self.changed.notify_all();
+        }
+    }
+
+    /// Stop the sender thread. Records still queued are discarded.
+    pub fn close(&self) {
+        if let Ok(mut queue) = self.lock() {
+            queue.closed = true;
+            self.changed.notify_all();
+        }
+    }
+
+    pub fn total_dropped_bytes(&self) -> u64 {
+        self.lock().map(|queue| queue.total_dropped_bytes).unwrap_or(0)
+    }
+
+    /// Wait until there is something to send and the frontend has room for
+    /// it, then encode the next batch. `None` once the stream is closed.
+    fn next_batch(&self) -> Option<Vec<u8>> {
+        let mut queue = self.lock().ok()?;
+        loop {
+            if queue.closed {
+                return None;
+            }
+            let in_flight = queue.next_batch - queue.acked_batches;
+            if !queue.records.is_empty() && in_flight < MAX_IN_FLIGHT {
+                break;
+            }
+            queue = self.changed.wait(queue).ok()?;
+        }
+
+        let mut records = Vec::new();
+        let mut batch_bytes = 0;
+        while let Some((record, _)) = queue.records.front() {
+            if !records.is_empty() && batch_bytes + record.data.len() > MAX_BATCH_BYTES {
+                break;
+            }
+            batch_bytes += record.data.len();
+            records.extend(queue.records.pop_front());
+        }
+        queue.queued_bytes -= batch_bytes;
+
+        let batch_id = queue.next_batch;
+        queue.next_batch += 1;
+        queue.sent_at.push_back(Instant::now());
+        let dropped = (
+            std::mem::take(&mut queue.dropped_bytes),
+            std::mem::take(&mut queue.dropped_records),
+        );
+        drop(queue);
+
+        Some(encode_batch(batch_id, dropped, &records))
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, StreamQueue>, String> {
+        self.queue.lock().map_err(|_| "Failed to lock stream mutex".to_string())
+    }
+}
+
+/// Send batches from `stream` over `channel` until the stream is closed or
+/// the frontend goes away.
+pub fn spawn_stream_sender(stream: Arc<RecordStream>, channel: Channel<InvokeResponseBody>) {
+    std::thread::spawn(move || {
+        while let Some(batch) = stream.next_batch() {
+            if let Err(e) = channel.send(InvokeResponseBody::Raw(batch)) {
+                println!("Stream channel {} closed: {}", channel.id(), e);
+                stream.close();
+                break;
+            }
+        }
+    });
+}
+
+/// Lay out a batch for the frontend. All integers are little-endian.
+///
+/// Batch header: batch id (u64), bytes dropped since the previous batch
+/// (u64), records dropped since the previous batch (u64), record count (u32).
+///
+/// Each record: seq (u64), direction (u8, 0 = RX, 1 = TX), channel (u8,
+/// 0 for single-port sessions), marker (u8, 0 = none, 1 = BREAK on,
+/// 2 = BREAK off), first byte monotonic ns (u64) and wall clock
+/// in Unix µs (i64), the same for the last byte, data length (u32), data.
+fn encode_batch(batch_id: u64, (dropped_bytes, dropped_records): (u64, u64), records: &[(Record, u8)]) -> Vec<u8> {
+    let data_len: usize = records.iter().map(|(record, _)| record.data.len()).sum();
+    let mut out = Vec::with_capacity(BATCH_HEADER_LEN + records.len() * RECORD_HEADER_LEN + data_len);
+    out.extend_from_slice(&batch_id.to_le_bytes());
+    out.extend_from_slice(&dropped_bytes.to_le_bytes());
+    out.extend_from_slice(&dropped_records.to_le_bytes());
+    out.extend_from_slice(&(records.len() as u32).to_le_bytes());
+
+    let put_timestamp = |out: &mut Vec<u8>, timestamp: &Timestamp| {
+        out.extend_from_slice(&timestamp.monotonic_ns.to_le_bytes());
+        out.extend_from_slice(&timestamp.wall_clock.timestamp_micros().to_le_bytes());
+    };
+    for (record, channel) in records {
+        out.extend_from_slice(&record.seq.to_le_bytes());
+        out.push(match record.direction {
+            Direction::Rx => 0,
+            Direction::Tx => 1,
+        });
+        out.push(*channel);
+        out.push(match record.marker {
+            None => 0,
+            Some(Marker::BreakOn) => 1,
+            Some(Marker::BreakOff) => 2,
+        });
+        put_timestamp(&mut out, &record.first_byte);
+        put_timestamp(&mut out, &record.last_byte);
+        out.extend_from_slice(&(record.data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&record.data);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(seq: u64, data: &[u8]) -> Record {
+        let now = Timestamp::now();
+        Record {
+            session_id: "main".to_string(),
+            seq,
+            direction: Direction::Rx,
+            first_byte: now,
+            last_byte: now,
+            data: data.to_vec(),
+            marker: None,
+        }
+    }
+
+    #[test]
+    fn test_stream_drops_when_full() {
+        let stream = RecordStream::new(4);
+        assert!(matches!(stream.push(record(0, b"abc"), 0), Push::Queued));
+        assert!(matches!(stream.push(record(1, b"de"), 0), Push::Dropped));
+        assert!(matches!(stream.push(record(2, b"f"), 0), Push::Queued));
+        assert_eq!(stream.total_dropped_bytes(), 2);
+
+        let batch = stream.next_batch().unwrap();
+        assert_eq!(batch.len(), BATCH_HEADER_LEN + 2 * RECORD_HEADER_LEN + 4);
+        assert_eq!(&batch[0..8], &0u64.to_le_bytes());
+        assert_eq!(&batch[8..16], &2u64.to_le_bytes());
+        assert_eq!(&batch[16..24], &1u64.to_le_bytes());
+        assert_eq!(&batch[24..28], &2u32.to_le_bytes());
+        // Second record's seq, right after the first record
+        let second = BATCH_HEADER_LEN + RECORD_HEADER_LEN + 3;
+        assert_eq!(&batch[second..second + 8], &2u64.to_le_bytes());
+        assert_eq!(&batch[batch.len() - 1..], b"f");
+
+        // The space freed by the batch can be used again
+        assert!(matches!(stream.push(record(3, b"ghij"), 0), Push::Queued));
+    }
+
+    #[test]
+    fn test_stream_hands_back_records_once_closed() {
+        let stream = RecordStream::new(4);
+        stream.close();
+        match stream.push(record(0, b"abc"), 0) {
+            Push::Closed(record) => assert_eq!(record.data, b"abc"),
+            other => panic!("expected the record back, got {:?}", other),
+        }
+        assert_eq!(stream.total_dropped_bytes(), 0);
+    }
+
+    #[test]
+    fn test_stream_closes_when_acks_stop() {
+        let stream = RecordStream::new(1024);
+        stream.lock().unwrap().ack_timeout = Duration::from_millis(20);
+        for seq in 0..MAX_IN_FLIGHT {
+            assert!(matches!(stream.push(record(seq, b"abc"), 0), Push::Queued));
+            stream.next_batch().unwrap();
+        }
+        stream.ack(0);
+        assert!(matches!(stream.push(record(4, b"d"), 0), Push::Queued));
+
+        // A reloaded frontend never acks the rest
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(matches!(stream.push(record(5, b"e"), 0), Push::Closed(_)));
+        assert!(stream.next_batch().is_none());
+    }
+}
@@ -0,0 +1,175 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file tap.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+use crate::commands::file::ProjectData;
+use crate::serial::config::{FlowControlSetting, ParitySetting, SerialConfig, StopBitsSetting};
+use serde::{Deserialize, Serialize};
+
+/// COMMSETTINGS mode value Docklight writes for monitoring projects
+/// (0 is plain send/receive).
+const MODE_MONITORING: &str = "1";
+
+/// The two ports of a monitored link. Both carry the same line settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TapSettings {
+    pub channels: [SerialConfig; 2],
+}
+
+impl TapSettings {
+    /// Read monitoring settings from a project's COMMSETTINGS, laid out as
+    /// mode, first port, second port, then the line settings read by
+    /// `line_settings`. Returns `None` unless the project selects monitoring
+    /// mode or a VersaTap device.
+    ///
+    /// Version 7 projects store bare port numbers, which map to `COMn`.
+    pub fn from_project(project: &ProjectData) -> Option<Self> {
+        let params = &project.comm_settings.params;
+        let monitoring = params.first().map(|m| m.trim()) == Some(MODE_MONITORING)
+            || project.versatap.unwrap_or(0) != 0;
+        if !monitoring || params.len() < 4 {
+            return None;
+        }
+
+        let line = line_settings(&params[3..])?;
+        let channel = |value: &str| {
+            let value = value.trim();
+            let port_name = if value.parse::<u32>().is_ok() { format!("COM{}", value) } else { value.to_string() };
+            SerialConfig { port_name, ..line.clone() }
+        };
+
+        Some(Self { channels: [channel(&params[1]), channel(&params[2])] })
+    }
+}
+
+/// Parse baud rate, parity, parity error character, data bits, stop bits
+/// and flow control as Docklight stores them. Parity is an index into
+/// even/mark/none/odd/space, data bits count up from 4, stop bits are
+/// 1/1.5/2 and flow control is off/manual/hardware/software/RS485; manual
+/// and RS485 leave the lines to the user, so they open without flow
+/// control. Settings missing from short parameter lists default to 8N1.
+fn line_settings(params: &[String]) -> Option<SerialConfig> {
+    let index = |i: usize, default: usize| -> Option<usize> {
+        params.get(i).map_or(Some(default), |value| value.trim().parse().ok())
+    };
+
+    let baud_rate = params.first()?.trim().parse().ok()?;
+    let parity = match index(1, 2)? {
+        0 => ParitySetting::Even,
+        1 => ParitySetting::Mark,
+        2 => ParitySetting::None,
+        3 => ParitySetting::Odd,
+        4 => ParitySetting::Space,
+        _ => return None,
+    };
+    let data_bits = match index(3, 4)? {
+        i @ 1..=4 => i as u8 + 4,
+        _ => return None,
+    };
+    let stop_bits = match index(4, 0)? {
+        0 => StopBitsSetting::One,
+        1 => StopBitsSetting::OnePointFive,
+        2 => StopBitsSetting::Two,
+        _ => return None,
+    };
+    let flow_control = match index(5, 0)? {
+        0 | 1 | 4 => FlowControlSetting::None,
+        2 => FlowControlSetting::Hardware,
+        3 => FlowControlSetting::Software,
+        _ => return None,
+    };
+
+    Some(SerialConfig { parity, data_bits, stop_bits, flow_control, ..SerialConfig::new("", baud_rate) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::file::CommSettings;
+
+    fn project(params: &[&str], versatap: Option<i32>) -> ProjectData {
+        ProjectData {
+            version: 8,
+            comm_settings: CommSettings { params: params.iter().map(|p| p.to_string()).collect() },
+            comm_display: 0,
+            comm_channels: vec![],
+            send_commands: vec![],
+            receive_commands: vec![],
+            versatap,
+            channel_alias: vec![],
+            simulator_rules: vec![],
+        }
+    }
+
+    fn channels(settings: Option<TapSettings>) -> Option<[String; 2]> {
+        settings.map(|settings| settings.channels.map(|config| config.port_name))
+    }
+
+    #[test]
+    fn test_tap_settings_from_project() {
+        let send_receive = project(&["0", "COM3", "COM2", "57600", "2", "63", "4", "0", "0"], Some(0));
+        assert_eq!(TapSettings::from_project(&send_receive), None);
+
+        let monitoring = project(&["1", "COM3", "COM2", "57600", "2", "63", "4", "0", "0"], Some(0));
+        let settings = TapSettings::from_project(&monitoring).unwrap();
+        assert_eq!(settings.channels[0], SerialConfig::new("COM3", 57600));
+        assert_eq!(settings.channels[1], SerialConfig::new("COM2", 57600));
+
+        let v7 = project(&["1", "1", "2", "9600", "2", "63", "4", "0", "0"], None);
+        assert_eq!(channels(TapSettings::from_project(&v7)), Some(["COM1".to_string(), "COM2".to_string()]));
+    }
+
+    #[test]
+    fn test_tap_line_settings_from_project() {
+        // 19200 baud, even parity, 7 data bits, 2 stop bits, hardware flow control
+        let monitoring = project(&["1", "COM3", "COM2", "19200", "0", "63", "3", "2", "2"], Some(0));
+        let settings = TapSettings::from_project(&monitoring).unwrap();
+        for (config, port_name) in settings.channels.iter().zip(["COM3", "COM2"]) {
+            assert_eq!(
+                *config,
+                SerialConfig {
+                    port_name: port_name.to_string(),
+                    baud_rate: 19200,
+                    data_bits: 7,
+                    stop_bits: StopBitsSetting::Two,
+                    parity: ParitySetting::Even,
+                    flow_control: FlowControlSetting::Hardware,
+                }
+            );
+        }
+
+        // Short parameter lists fall back to 8N1
+        let short = project(&["1", "COM3", "COM2", "4800"], None);
+        assert_eq!(TapSettings::from_project(&short).unwrap().channels[0], SerialConfig::new("COM3", 4800));
+
+        let bad_parity = project(&["1", "COM3", "COM2", "9600", "9", "63", "4", "0", "0"], None);
+        assert_eq!(TapSettings::from_project(&bad_parity), None);
+    }
+}
@@ -0,0 +1,326 @@
+/*
+ * termLight - Serial Command Manager
+ * 
+ * Copyright (c) 2025 Marc Ledesma
+ * 
+ * This project is licensed under the GNU General Public License v3.0
+ * See LICENSE file for details or visit: https://www.gnu.org/licenses/gpl-3.0.html
+ * 
+ * WARNING: Approximately 80% of this codebase was generated using AI assistance.
+ * Please review, test, and validate all code before use in production environments.
+ * 
+ * Description: A serial communication tool for sending, receiving, 
+ * and managing commands via COM ports.
+ * 
+ * GitHub: https://github.com/marcledesma/termLight
+ * 
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ * 
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ * 
+ * @file virtual_port.rs
+ * @author Marc Ledesma
+ * @date 2026-10-18
+ */
+
+
+use crate::serial::port::{Transport, READ_TIMEOUT};
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Hands everything written straight back to the read side, like a
+/// TX-RX jumper on a real adapter.
+pub struct LoopbackTransport {
+    shared: Arc<Loop>,
+    timeout: Duration,
+}
+
+#[derive(Default)]
+struct Loop {
+    buffer: Mutex<VecDeque<u8>>,
+    ready: Condvar,
+}
+
+impl Default for LoopbackTransport {
+    fn default() -> Self {
+        Self {
+            shared: Arc::default(),
+            timeout: READ_TIMEOUT,
+        }
+    }
+}
+
+impl Read for LoopbackTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let deadline = Instant::now() + self.timeout;
+        let mut buffer = self.shared.buffer.lock().map_err(|_| io::Error::other("Failed to lock loopback mutex"))?;
+        while buffer.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(io::Error::from(io::ErrorKind::TimedOut));
+            }
+            buffer = self
+                .shared
+                .ready
+                .wait_timeout(buffer, deadline - now)
+                .map_err(|_| io::Error::other("Failed to lock loopback mutex"))?
+                .0;
+        }
+
+        let n = buf.len().min(buffer.len());
+        for (slot, byte) in buf.iter_mut().zip(buffer.drain(..n)) {
+            *slot = byte;
+        }
+        Ok(n)
+    }
+}
+
+impl Write for LoopbackTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut buffer = self.shared.buffer.lock().map_err(|_| io::Error::other("Failed to lock loopback mutex"))?;
+        buffer.extend(buf);
+        self.shared.ready.notify_all();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for LoopbackTransport {
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(LoopbackTransport {
+            shared: self.shared.clone(),
+            timeout: self.timeout,
+        }))
+    }
+
+    fn clear_input(&self) -> io::Result<()> {
+        self.shared
+            .buffer
+            .lock()
+            .map_err(|_| io::Error::other("Failed to lock loopback mutex"))?
+            .clear();
+        Ok(())
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+}
+
+/// The master side of a pseudo-terminal pair. Other programs open the slave
+/// path (`/dev/pts/N`) as if it were a serial port and talk to the session
+/// through it.
+#[cfg(target_os = "linux")]
+pub struct PtyTransport {
+    master: std::fs::File,
+    /// Held open so reads don't fail with EIO while no program has the
+    /// slave open
+    _slave: Arc<std::fs::File>,
+    path: String,
+    timeout: Duration,
+    /// How long a write waits for the slave side to drain before giving up
+    write_timeout: Duration,
+}
+
+/// Nothing drains the pseudo-terminal while no program has the slave open,
+/// so writes give up after this long instead of holding the port forever.
+#[cfg(target_os = "linux")]
+const PTY_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[cfg(target_os = "linux")]
+impl PtyTransport {
+    pub fn open() -> Result<Self, String> {
+        use std::os::unix::io::{AsRawFd, FromRawFd};
+
+        let mut master = -1;
+        let mut slave = -1;
+        // SAFETY: openpty only writes the two descriptors; name, termios and
+        // winsize may be null. Both descriptors are owned by Files right after
+        let (master, slave) = unsafe {
+            if libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null(), std::ptr::null()) != 0 {
+                return Err(format!("Failed to create pseudo-terminal: {}", io::Error::last_os_error()));
+            }
+            (std::fs::File::from_raw_fd(master), std::fs::File::from_raw_fd(slave))
+        };
+
+        let mut name = [0 as libc::c_char; 64];
+        // SAFETY: ptsname_r writes a NUL-terminated path of at most name.len()
+        // bytes into the buffer
+        let path = unsafe {
+            let err = libc::ptsname_r(master.as_raw_fd(), name.as_mut_ptr(), name.len());
+            if err != 0 {
+                return Err(format!("Failed to resolve pseudo-terminal path: {}", io::Error::from_raw_os_error(err)));
+            }
+            std::ffi::CStr::from_ptr(name.as_ptr()).to_string_lossy().to_string()
+        };
+        make_raw(&slave)?;
+
+        // Non-blocking so a full slave queue turns into a write timeout
+        // SAFETY: the descriptor is owned by `master` and open for the call
+        unsafe {
+            let flags = libc::fcntl(master.as_raw_fd(), libc::F_GETFL);
+            if flags < 0 || libc::fcntl(master.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK) != 0 {
+                return Err(format!("Failed to make pseudo-terminal non-blocking: {}", io::Error::last_os_error()));
+            }
+        }
+
+        Ok(Self {
+            master,
+            _slave: Arc::new(slave),
+            path,
+            timeout: READ_TIMEOUT,
+            write_timeout: PTY_WRITE_TIMEOUT,
+        })
+    }
+
+    /// Path other programs open to reach this session.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Wait until the master is ready for `events`, or fail with `TimedOut`.
+    fn wait(&self, events: libc::c_short, timeout: Duration) -> io::Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        let mut poll_fd = libc::pollfd {
+            fd: self.master.as_raw_fd(),
+            events,
+            revents: 0,
+        };
+        let timeout_ms = timeout.as_millis().clamp(1, i32::MAX as u128) as i32;
+        // SAFETY: poll_fd is a single valid pollfd for the length passed
+        match unsafe { libc::poll(&mut poll_fd, 1, timeout_ms) } {
+            0 => Err(io::Error::from(io::ErrorKind::TimedOut)),
+            n if n < 0 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Pass bytes through untouched: no echo, line editing or CR/LF mapping.
+#[cfg(target_os = "linux")]
+fn make_raw(tty: &std::fs::File) -> Result<(), String> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: `tty` stays open for the whole call, and termios is plain data
+    // that tcgetattr fully initializes
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(tty.as_raw_fd(), &mut termios) != 0 {
+            return Err(format!("Failed to read pseudo-terminal attributes: {}", io::Error::last_os_error()));
+        }
+        libc::cfmakeraw(&mut termios);
+        if libc::tcsetattr(tty.as_raw_fd(), libc::TCSANOW, &termios) != 0 {
+            return Err(format!("Failed to set pseudo-terminal to raw mode: {}", io::Error::last_os_error()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+impl Read for PtyTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.wait(libc::POLLIN, self.timeout)?;
+        match self.master.read(buf) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Err(io::Error::from(io::ErrorKind::TimedOut)),
+            result => result,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Write for PtyTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let deadline = Instant::now() + self.write_timeout;
+        loop {
+            match self.master.write(buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Err(io::Error::from(io::ErrorKind::TimedOut));
+                    }
+                    self.wait(libc::POLLOUT, remaining)?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.master.flush()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Transport for PtyTransport {
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(PtyTransport {
+            master: self.master.try_clone()?,
+            _slave: self._slave.clone(),
+            path: self.path.clone(),
+            timeout: self.timeout,
+            write_timeout: self.write_timeout,
+        }))
+    }
+
+    fn clear_input(&self) -> io::Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        // SAFETY: the descriptor is open for as long as `self` is
+        if unsafe { libc::tcflush(self.master.as_raw_fd(), libc::TCIFLUSH) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_echoes_writes() {
+        let mut port = LoopbackTransport::default();
+        let mut reader = port.try_clone_transport().unwrap();
+        reader.set_read_timeout(Duration::from_millis(10)).unwrap();
+
+        let mut buf = [0u8; 8];
+        assert_eq!(reader.read(&mut buf).unwrap_err().kind(), io::ErrorKind::TimedOut);
+        port.write_all(b"ping").unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap(), 4);
+        assert_eq!(&buf[..4], b"ping");
+
+        port.write_all(b"stale").unwrap();
+        port.clear_input().unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap_err().kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_pty_write_times_out_without_reader() {
+        let mut pty = PtyTransport::open().unwrap();
+        pty.write_timeout = Duration::from_millis(20);
+
+        // Nothing reads the slave, so its queue fills and writes give up
+        let chunk = [0x55u8; 4096];
+        let err = (0..1024).find_map(|_| pty.write_all(&chunk).err()).expect("pty never filled up");
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -55,6 +55,8 @@ function App() {
   const editingCommandId = useStore((state) => state.editingCommandId);
   const refreshPorts = useStore((state) => state.refreshPorts);
   const initRecentProjects = useStore((state) => state.initRecentProjects);
+  const syncConnectionStatus = useStore((state) => state.syncConnectionStatus);
+  const handleConnectionState = useStore((state) => state.handleConnectionState);
   const appendLog = useStore((state) => state.appendLog);
   const theme = useStore((state) => state.theme);
   const commandPanelWidth = useStore((state) => state.commandPanelWidth);
@@ -80,25 +82,40 @@ function App() {
     refreshPorts();
     // Load recent projects
     initRecentProjects();
+    // The backend may still hold a session from before a reload
+    syncConnectionStatus();
 
     // Listen for incoming data
     console.log("Initializing data listener in App");
-    const unlistenPromise = serialService.listenToData((data) => {
-      console.log("App received data:", data);
+    // The backend reports what was sent as well, so the log keeps its order
+    const unlistenPromise = serialService.listenToRecords((record) => {
+      console.log("App received record:", record);
       appendLog({
-        timestamp: Date.now(),
-        direction: 'rx',
-        data: Array.from(data), // Convert Uint8Array to regular array for storage
+        timestamp: Date.parse(record.first_byte.wall_clock),
+        direction: record.direction === 'RX' ? 'rx' : 'tx',
+        data: record.data,
+        ...(record.marker && { note: record.marker === 'break-on' ? 'BREAK on' : 'BREAK off' }),
       });
     });
 
+    // Keep the port picker current as adapters are plugged and unplugged
+    const unlistenPortsPromise = serialService.listenToPortChanges(
+      () => refreshPorts(),
+      () => refreshPorts()
+    );
+
+    // Follow the backend when a session drops, errors or comes back
+    const unlistenStatePromise = serialService.listenToConnectionState(handleConnectionState);
+
     return () => {
       unlistenPromise.then((fn) => {
         console.log("Cleaning up data listener");
         fn();
       });
+      unlistenPortsPromise.then((fn) => fn());
+      unlistenStatePromise.then((fn) => fn());
     };
-  }, [refreshPorts, appendLog, initRecentProjects]);
+  }, [refreshPorts, appendLog, initRecentProjects, syncConnectionStatus, handleConnectionState]);
 
   return (
     <div className={clsx("flex flex-col h-screen", { "dark": theme === "Dark" })}>
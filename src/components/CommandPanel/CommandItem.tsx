@@ -35,6 +35,7 @@ import { Command } from '../../types';
 import { Button } from '../Common/Button';
 import { useStore } from '../../store';
 import { commandService } from '../../services/commandService';
+import { serialService } from '../../services/serialService';
 
 interface CommandItemProps {
   command: Command;
@@ -43,7 +44,7 @@ interface CommandItemProps {
 }
 
 export function CommandItem({ command, index, isDragDisabled }: CommandItemProps) {
-  const { setSelectedCommand, sendSerialData, appendLog, isConnected, setActiveModal, setEditingCommandId, setCommandToDeleteId, commandColumnWidths } = useStore();
+  const { setSelectedCommand, sendSerialData, isConnected, setActiveModal, setEditingCommandId, setCommandToDeleteId, commandColumnWidths } = useStore();
 
   const handleSend = async (e: React.MouseEvent) => {
     e.stopPropagation(); // Prevent selecting the item when clicking send
@@ -54,13 +55,12 @@ export function CommandItem({ command, index, isDragDisabled }: CommandItemProps
       // We need to parse it back to bytes to send
       const data = commandService.parseHexSequence(command.sequence);
       
-      await sendSerialData(data);
-      
-      appendLog({
-        timestamp: Date.now(),
-        direction: 'tx',
-        data: Array.from(data),
-      });
+      if (commandService.hasSequenceSteps(command.sequence)) {
+        // BRK steps need the backend to drive the line between the bytes
+        await serialService.sendSequence(command.sequence);
+      } else {
+        await sendSerialData(data);
+      }
     } catch (error) {
       console.error("Failed to send command:", error);
     }
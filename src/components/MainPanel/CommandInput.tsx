@@ -45,7 +45,6 @@ export function CommandInput() {
     setLineEnding, 
     isConnected, 
     sendSerialData, 
-    appendLog,
     inputFormat,
     setInputFormat,
     cobsEnabled,
@@ -98,12 +97,6 @@ export function CommandInput() {
 
       await sendSerialData(dataToSend);
       
-      appendLog({
-        timestamp: Date.now(),
-        direction: 'tx',
-        data: Array.from(dataToSend), // Convert Uint8Array to regular array for storage
-      });
-      
       setInputValue('');
     } catch (error) {
       console.error("Error parsing input:", error);
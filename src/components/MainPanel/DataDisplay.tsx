@@ -121,8 +121,17 @@ export function DataDisplay() {
       )}
       
       {dataLog.map((entry, index) => {
-        const { formatted, crcValid } = renderData(entry.data);
         const timestamp = new Date(entry.timestamp).toLocaleTimeString();
+        if (entry.note) {
+          return (
+            <div key={index} className="mb-1 italic text-gray-500 dark:text-gray-400">
+              <span className={clsx("select-none mr-2", metaFontSizeClass)}>[{timestamp}]</span>
+              {entry.note}
+            </div>
+          );
+        }
+
+        const { formatted, crcValid } = renderData(entry.data);
         const color = entry.direction === 'rx' ? displayColors.receive : displayColors.send;
         
         // Docklight style: structured log
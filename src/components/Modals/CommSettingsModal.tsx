@@ -35,7 +35,9 @@ import { X } from 'lucide-react';
 import { Button } from '../Common/Button';
 import { Dropdown } from '../Common/Dropdown';
 import { useStore } from '../../store';
-import { SerialConfig } from '../../types';
+import { LineCapabilities, SerialConfig } from '../../types';
+import { serialService } from '../../services/serialService';
+import { FLOW_CONTROL_OPTIONS, PARITY_OPTIONS, STOP_BITS } from '../../utils/constants';
 
 export function CommSettingsModal() {
   const { 
@@ -45,6 +47,7 @@ export function CommSettingsModal() {
     parity, 
     dataBits, 
     stopBits,
+    flowControl,
     availablePorts,
     setSerialConfig,
     refreshPorts 
@@ -55,13 +58,26 @@ export function CommSettingsModal() {
     baudRate,
     parity,
     dataBits,
-    stopBits
+    stopBits,
+    flowControl
+  });
+
+  // Only offer parity and stop bits the backend can set on this platform
+  const [capabilities, setCapabilities] = useState<LineCapabilities>({
+    parity: ['None', 'Even', 'Odd'],
+    stopBits: [1, 2],
   });
 
   useEffect(() => {
     refreshPorts();
   }, [refreshPorts]);
 
+  useEffect(() => {
+    serialService.getLineCapabilities()
+      .then(setCapabilities)
+      .catch((err) => console.error('Failed to get line capabilities:', err));
+  }, []);
+
   const handleSave = () => {
     setSerialConfig(localConfig);
     setActiveModal(null);
@@ -110,9 +126,9 @@ export function CommSettingsModal() {
             value={localConfig.parity}
             onChange={(e) => setLocalConfig({...localConfig, parity: e.target.value as any})}
           >
-            <option value="None">None</option>
-            <option value="Even">Even</option>
-            <option value="Odd">Odd</option>
+            {PARITY_OPTIONS.filter((p) => capabilities.parity.includes(p)).map((p) => (
+              <option key={p} value={p}>{p}</option>
+            ))}
           </Dropdown>
           <Dropdown 
             label="Data Bits" 
@@ -129,9 +145,21 @@ export function CommSettingsModal() {
             value={localConfig.stopBits}
             onChange={(e) => setLocalConfig({...localConfig, stopBits: Number(e.target.value) as any})}
           >
-            <option value="1">1</option>
-            <option value="1.5">1.5</option>
-            <option value="2">2</option>
+            {STOP_BITS.filter((s) => capabilities.stopBits.includes(s))
+              // 1.5 stop bits only exist for 5-bit characters
+              .filter((s) => s !== 1.5 || Number(localConfig.dataBits) === 5)
+              .map((s) => (
+                <option key={s} value={s}>{s}</option>
+              ))}
+          </Dropdown>
+          <Dropdown 
+            label="Flow Control" 
+            value={localConfig.flowControl}
+            onChange={(e) => setLocalConfig({...localConfig, flowControl: e.target.value as any})}
+          >
+            {FLOW_CONTROL_OPTIONS.map((f) => (
+              <option key={f} value={f}>{f}</option>
+            ))}
           </Dropdown>
         </div>
         <div className="flex justify-end gap-2 p-4 border-t border-gray-200 dark:border-gray-700">
@@ -75,11 +75,12 @@ export const commandService = {
       errors.push('Command sequence is required');
     }
 
-    // Validate hex sequence format (space-separated hex bytes)
+    // Validate hex sequence format (space-separated hex bytes, optionally with BRK steps)
     if (command.sequence) {
-      const hexPattern = /^[0-9A-Fa-f]{2}(\s+[0-9A-Fa-f]{2})*$/;
-      if (!hexPattern.test(command.sequence.trim())) {
-        errors.push('Command sequence must be space-separated hex bytes (e.g., "2D 2D 6F")');
+      const token = '(?:[0-9A-Fa-f]{2}|BRK(?::\\d+)?)';
+      const sequencePattern = new RegExp(`^${token}(\\s+${token})*$`, 'i');
+      if (!sequencePattern.test(command.sequence.trim())) {
+        errors.push('Command sequence must be space-separated hex bytes (e.g., "2D 2D 6F"), optionally with BRK or BRK:<ms> steps');
       }
     }
 
@@ -97,10 +98,17 @@ export const commandService = {
   },
 
   /**
-   * Parse hex sequence from DochLight format to bytes
+   * Whether a sequence contains steps (such as BRK) that only the backend can execute
+   */
+  hasSequenceSteps: (sequence: string): boolean => {
+    return /(^|\s)BRK(:\d+)?(?=\s|$)/i.test(sequence.trim());
+  },
+
+  /**
+   * Parse hex sequence from DochLight format to bytes (BRK steps are skipped)
    */
   parseHexSequence: (hexString: string): Uint8Array => {
-    const hexBytes = hexString.trim().split(/\s+/);
+    const hexBytes = hexString.trim().split(/\s+/).filter((token) => /^[0-9A-Fa-f]{2}$/.test(token));
     const bytes = new Uint8Array(hexBytes.length);
     
     for (let i = 0; i < hexBytes.length; i++) {
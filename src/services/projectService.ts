@@ -64,6 +64,13 @@ interface ProjectData {
   }[];
   versatap?: number; // v8+ optional field
   channel_alias?: string[]; // v8+ optional field
+  simulator_rules?: {
+    receive_index: number;
+    send_index: number;
+    delay_ms: number;
+    enabled: boolean;
+    max_answers: number;
+  }[]; // termLight only
 }
 
 /**
@@ -196,6 +203,13 @@ export const projectService = {
         param5: cmd.param5,
         param6: cmd.param6,
       })),
+      simulator_rules: (project.simulatorRules || []).map((rule) => ({
+        receive_index: rule.receiveIndex,
+        send_index: rule.sendIndex,
+        delay_ms: rule.delayMs,
+        enabled: rule.enabled,
+        max_answers: rule.maxAnswers,
+      })),
     };
   },
 
@@ -261,6 +275,13 @@ export const projectService = {
       commSettings,
       commDisplay: data.comm_display,
       commChannels: data.comm_channels,
+      simulatorRules: (data.simulator_rules || []).map((rule) => ({
+        receiveIndex: rule.receive_index,
+        sendIndex: rule.send_index,
+        delayMs: rule.delay_ms,
+        enabled: rule.enabled,
+        maxAnswers: rule.max_answers,
+      })),
     };
   },
 
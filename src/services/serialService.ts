@@ -30,44 +30,628 @@
  * @date 2025-11-19
  */
 
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import { SerialConfig, PortInfo } from '../types';
+import { SerialConfig, PortInfo, Project, ReceiveCommand, LineCapabilities } from '../types';
+import { projectService } from './projectService';
 
+export type Direction = 'RX' | 'TX';
+
+// Line conditions recorded in place in the data stream
+export type Marker = 'break-on' | 'break-off';
+
+export interface Timestamp {
+  // Nanoseconds since the backend started; use for intervals
+  monotonic_ns: number;
+  // RFC 3339 local time; use for display
+  wall_clock: string;
+}
+
+// One frame received or sent on a session; seq orders RX and TX together
 export interface SerialPayload {
+  session_id: string;
+  seq: number;
+  direction: Direction;
+  first_byte: Timestamp;
+  last_byte: Timestamp;
+  data: number[];
+  // Side of a two-port session (1 or 2), 0 for single-port sessions; set on streamed records
+  channel?: number;
+  // Set on records that mark a line condition; these carry no data
+  marker?: Marker;
+}
+
+export interface ClientPayload {
+  session_id: string;
+  address: string;
+}
+
+export interface ChannelPayload extends SerialPayload {
+  channel: 1 | 2;
+  port_name: string;
+}
+
+export interface TapSettings {
+  // Both ports share the link's line settings
+  channels: [SerialConfig, SerialConfig];
+}
+
+export interface BridgeSettings {
+  // portName takes the same endpoint syntax as connect (COM3, TCP:host:port, SERVER:port, ...)
+  endpoints: [SerialConfig, SerialConfig];
+  // Only groups the recorded bytes; forwarding never waits for a frame
+  framing?: FramingConfig | null;
+}
+
+export type OutputLine = 'DTR' | 'RTS';
+
+export interface ModemLines {
+  cts: boolean;
+  dsr: boolean;
+  dcd: boolean;
+  ri: boolean;
+}
+
+export interface ModemLinesPayload extends ModemLines {
+  session_id: string;
+  timestamp: string;
+}
+
+export interface ReceiveMatchPayload {
+  session_id: string;
+  command_index: number;
+  name: string;
+  // Position of the first matched byte among all bytes received on the channel
+  offset: number;
   data: number[];
+  // Bytes that filled the sequence's ? and # wildcards, in order; null for
+  // a # that matched nothing
+  wildcards: (number | null)[];
+  channel: number;
+  // Record the match ended in
+  seq: number;
+  timestamp: Timestamp;
+}
+
+export interface OpenPortRemovedPayload {
+  session_id: string;
+  port: PortInfo;
+}
+
+export interface ReconnectPolicy {
+  initial_delay_ms?: number;
+  max_delay_ms?: number;
+  max_attempts?: number | null;
+}
+
+// How received bytes are split into serial-payload events (default: 5 ms idle gap)
+export type FramingConfig =
+  | { type: 'idle_gap'; timeout_ms: number }
+  | { type: 'delimiter'; delimiter: number[]; include_delimiter?: boolean; max_length?: number }
+  | { type: 'fixed_length'; length: number }
+  | {
+      type: 'length_prefixed';
+      length_offset?: number;
+      length_size: 1 | 2 | 4;
+      big_endian?: boolean;
+      length_adjustment?: number;
+      max_length?: number;
+    }
+  | { type: 'raw' };
+
+export interface DisconnectedPayload {
+  session_id: string;
+  reason: string;
+  reconnecting: boolean;
+}
+
+export interface ReconnectedPayload {
+  session_id: string;
+  port_name: string;
+  attempts: number;
+}
+
+export interface ReconnectFailedPayload {
+  session_id: string;
+  attempts: number;
+}
+
+export interface SessionInfo {
+  session_id: string;
+  port_name: string;
+}
+
+export type ConnectionState =
+  | { state: 'opening' }
+  | { state: 'open' }
+  | { state: 'error'; message: string; kind: string }
+  | { state: 'reconnecting' }
+  | { state: 'closed' };
+
+export type ConnectionStatePayload = ConnectionState & {
+  session_id: string;
+  port_name: string;
+  timestamp: string;
+};
+
+export type ConnectionStatus = ConnectionState & {
+  session_id: string;
+  port_name: string;
+  is_connected: boolean;
+  emit_failures: number;
+  dropped_bytes: number;
+};
+
+export interface HistoryLimits {
+  max_bytes?: number;
+  max_frames?: number;
+}
+
+// Bounds are inclusive; times are RFC 3339 strings
+export interface HistoryQuery {
+  from_seq?: number;
+  to_seq?: number;
+  from_time?: string;
+  to_time?: string;
+  direction?: Direction;
+  limit?: number;
+}
+
+export interface HistoryInfo {
+  frames: number;
+  bytes: number;
+  first_seq: number | null;
+  last_seq: number | null;
+  evicted_frames: number;
+  limits: Required<HistoryLimits>;
 }
 
+export interface SearchMatch {
+  seq: number;
+  offset: number;
+}
+
+export type LogFormat = 'ascii' | 'hex' | 'decimal' | 'binary';
+
+export interface LogSettings {
+  directory: string;
+  base_name?: string;
+  formats: LogFormat[];
+  sync_interval_ms?: number;
+  // Rotation: new segment by size and/or age, optional gzip, keep the newest max_segments
+  max_segment_bytes?: number | null;
+  max_segment_secs?: number | null;
+  gzip?: boolean;
+  max_segments?: number | null;
+  // Placeholders: {base} {port} {timestamp} {index} {format}
+  name_template?: string | null;
+}
+
+export interface LogStatus {
+  files: string[];
+  segment_index: number;
+  paused: boolean;
+  bytes_logged: number;
+  started_at: string;
+  error: string | null;
+}
+
+export interface SimulatorRuleStatus {
+  receive_index: number;
+  send_index: number;
+  enabled: boolean;
+  matches: number;
+  answers: number;
+}
+
+export type ReplayMode = { mode: 'timed'; speed: number } | { mode: 'step' };
+
+export type ReplayStatus = ReplayMode & {
+  position: number;
+  total: number;
+  finished: boolean;
+};
+
+// Session used by the single-port UI; additional sessions pick their own ids
+export const DEFAULT_SESSION_ID = 'main';
+
+type RecordListener = (record: SerialPayload) => void;
+
+// Listeners fed from attached streams; event-delivered records reach them through listen()
+const recordListeners = new Set<RecordListener>();
+
+// The backend's SerialConfig; form values may arrive as strings
+const toBackendConfig = (config: SerialConfig) => ({
+  portName: config.portName,
+  baudRate: Number(config.baudRate),
+  dataBits: Number(config.dataBits),
+  stopBits: Number(config.stopBits),
+  parity: config.parity,
+  flowControl: config.flowControl ?? 'None',
+});
+
+// Decode one binary stream batch; see encode_batch in src-tauri/src/serial/stream.rs
+const decodeBatch = (sessionId: string, buffer: ArrayBuffer) => {
+  const view = new DataView(buffer);
+  const bytes = new Uint8Array(buffer);
+  const u64 = (offset: number) => Number(view.getBigUint64(offset, true));
+  const timestamp = (offset: number): Timestamp => ({
+    monotonic_ns: u64(offset),
+    wall_clock: new Date(Number(view.getBigInt64(offset + 8, true)) / 1000).toISOString(),
+  });
+
+  const batchId = u64(0);
+  const droppedBytes = u64(8);
+  const droppedRecords = u64(16);
+  const count = view.getUint32(24, true);
+  const records: SerialPayload[] = [];
+  let offset = 28;
+  for (let i = 0; i < count; i++) {
+    const length = view.getUint32(offset + 43, true);
+    const marker = view.getUint8(offset + 10);
+    records.push({
+      session_id: sessionId,
+      seq: u64(offset),
+      direction: view.getUint8(offset + 8) === 0 ? 'RX' : 'TX',
+      channel: view.getUint8(offset + 9),
+      first_byte: timestamp(offset + 11),
+      last_byte: timestamp(offset + 27),
+      data: Array.from(bytes.subarray(offset + 47, offset + 47 + length)),
+      ...(marker !== 0 && { marker: marker === 1 ? 'break-on' : 'break-off' }),
+    });
+    offset += 47 + length;
+  }
+  return { batchId, droppedBytes, droppedRecords, records };
+};
+
 export const serialService = {
   listPorts: async (): Promise<PortInfo[]> => {
     return await invoke('list_ports');
   },
 
-  connect: async (config: SerialConfig): Promise<void> => {
+  getLineCapabilities: async (): Promise<LineCapabilities> => {
+    return await invoke('get_line_capabilities');
+  },
+
+  // Pass a reconnect policy ({} for the defaults) to have the backend reopen the port after a drop
+  connect: async (
+    config: SerialConfig,
+    sessionId: string = DEFAULT_SESSION_ID,
+    reconnect: ReconnectPolicy | null = null,
+    framing: FramingConfig | null = null
+  ): Promise<void> => {
     console.log('Connecting to port:', config);
     await invoke('open_port', {
-      portName: config.portName,
-      baudRate: Number(config.baudRate),
-      dataBits: Number(config.dataBits),
-      stopBits: Number(config.stopBits),
-      parity: config.parity,
+      sessionId,
+      config: toBackendConfig(config),
+      reconnect,
+      framing,
     });
+    try {
+      await serialService.attachStream(sessionId);
+    } catch (error) {
+      // Don't leave the port open behind a failed connect
+      await invoke('close_port', { sessionId }).catch(() => {});
+      throw error;
+    }
   },
 
-  disconnect: async (): Promise<void> => {
-    await invoke('close_port');
+  // Switch the session from JSON events to batched binary delivery; records
+  // still reach listenToRecords/listenToData callbacks
+  attachStream: async (
+    sessionId: string = DEFAULT_SESSION_ID,
+    onDropped?: (droppedBytes: number, droppedRecords: number) => void,
+    bufferBytes: number | null = null
+  ): Promise<void> => {
+    const onData = new Channel<ArrayBuffer>();
+    onData.onmessage = (buffer) => {
+      const batch = decodeBatch(sessionId, buffer);
+      if (batch.droppedBytes > 0) {
+        console.warn(`Session ${sessionId} dropped ${batch.droppedBytes} bytes (frontend too slow)`);
+        onDropped?.(batch.droppedBytes, batch.droppedRecords);
+      }
+      batch.records.forEach((record) => recordListeners.forEach((listener) => listener(record)));
+      // Acknowledge only after processing so a busy UI slows the backend down
+      invoke('ack_stream', { sessionId, batchId: batch.batchId }).catch((err) =>
+        console.error('Failed to acknowledge stream batch:', err)
+      );
+    };
+    await invoke('attach_stream', { sessionId, onData, bufferBytes });
   },
 
-  send: async (data: Uint8Array | number[]): Promise<void> => {
+  detachStream: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('detach_stream', { sessionId });
+  },
+
+  queryHistory: async (query: HistoryQuery = {}, sessionId: string = DEFAULT_SESSION_ID): Promise<SerialPayload[]> => {
+    return await invoke('query_history', { sessionId, query });
+  },
+
+  searchHistory: async (
+    pattern: Uint8Array | number[],
+    query: HistoryQuery = {},
+    sessionId: string = DEFAULT_SESSION_ID
+  ): Promise<SearchMatch[]> => {
+    return await invoke('search_history', { sessionId, pattern: Array.from(pattern), query });
+  },
+
+  clearHistory: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('clear_history', { sessionId });
+  },
+
+  getHistoryInfo: async (sessionId: string = DEFAULT_SESSION_ID): Promise<HistoryInfo> => {
+    return await invoke('get_history_info', { sessionId });
+  },
+
+  startLog: async (settings: LogSettings, sessionId: string = DEFAULT_SESSION_ID): Promise<LogStatus> => {
+    return await invoke('start_log', { sessionId, settings });
+  },
+
+  stopLog: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('stop_log', { sessionId });
+  },
+
+  pauseLog: async (paused: boolean, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('pause_log', { sessionId, paused });
+  },
+
+  getLogStatus: async (sessionId: string = DEFAULT_SESSION_ID): Promise<LogStatus | null> => {
+    return await invoke('get_log_status', { sessionId });
+  },
+
+  // Write recorded history as pcapng (raw bytes on DLT_USER0, one interface per session/channel)
+  exportPcapng: async (filePath: string, sessionIds: string[] = [DEFAULT_SESSION_ID], query: HistoryQuery = {}): Promise<number> => {
+    return await invoke('export_pcapng', { sessionIds, filePath, query });
+  },
+
+  startCapture: async (filePath: string, sessionIds: string[] = [DEFAULT_SESSION_ID]): Promise<void> => {
+    await invoke('start_capture', { sessionIds, filePath });
+  },
+
+  stopCapture: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('stop_capture', { sessionId });
+  },
+
+  setHistoryLimits: async (limits: HistoryLimits, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('set_history_limits', { sessionId, limits });
+  },
+
+  listenOnPort: async (
+    localPort: number,
+    sessionId: string = DEFAULT_SESSION_ID,
+    framing: FramingConfig | null = null
+  ): Promise<void> => {
+    await invoke('listen_port', { sessionId, localPort, framing });
+  },
+
+  disconnect: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('close_port', { sessionId });
+  },
+
+  send: async (data: Uint8Array | number[], sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
     // Tauri expects Vec<u8> as number[] or similar
-    await invoke('send_data', { data: Array.from(data) });
+    await invoke('send_data', { sessionId, data: Array.from(data) });
+  },
+
+  openTap: async (settings: TapSettings, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('open_tap', { sessionId, settings: { channels: settings.channels.map(toBackendConfig) } });
+  },
+
+  getTapSettings: async (project: unknown): Promise<TapSettings | null> => {
+    return await invoke('get_tap_settings', { project });
+  },
+
+  listenToTapData: async (callback: (payload: ChannelPayload) => void) => {
+    return await listen<ChannelPayload>('tap-payload', (event) => {
+      callback(event.payload);
+    });
+  },
+
+  openBridge: async (settings: BridgeSettings, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('open_bridge', {
+      sessionId,
+      settings: { endpoints: settings.endpoints.map(toBackendConfig), framing: settings.framing ?? null },
+    });
+  },
+
+  listenToBridgeData: async (callback: (payload: ChannelPayload) => void) => {
+    return await listen<ChannelPayload>('bridge-payload', (event) => {
+      callback(event.payload);
+    });
+  },
+
+  setModemLine: async (line: OutputLine, level: boolean, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('set_modem_line', { sessionId, line, level });
+  },
+
+  pulseModemLine: async (
+    line: OutputLine,
+    level: boolean,
+    durationMs: number,
+    sessionId: string = DEFAULT_SESSION_ID
+  ): Promise<void> => {
+    await invoke('pulse_modem_line', { sessionId, line, level, durationMs });
+  },
+
+  getModemLines: async (sessionId: string = DEFAULT_SESSION_ID): Promise<ModemLines> => {
+    return await invoke('get_modem_lines', { sessionId });
+  },
+
+  listenToModemLines: async (callback: (payload: ModemLinesPayload) => void) => {
+    return await listen<ModemLinesPayload>('modem-lines', (event) => {
+      callback(event.payload);
+    });
+  },
+
+  sendBreak: async (durationMs: number, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('send_break', { sessionId, durationMs });
+  },
+
+  setBreak: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('set_break', { sessionId });
+  },
+
+  clearBreak: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('clear_break', { sessionId });
+  },
+
+  // Sequence may mix hex bytes with BRK / BRK:<ms> steps
+  sendSequence: async (sequence: string, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('send_sequence', { sessionId, sequence });
+  },
+
+  // Answer received data with the project's simulator rules, playing the device
+  startSimulator: async (project: Project, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('start_simulator', { sessionId, project: projectService.toProjectData(project) });
+  },
+
+  stopSimulator: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('stop_simulator', { sessionId });
   },
 
-  listenToData: async (callback: (data: Uint8Array) => void) => {
+  // rule is the rule's position in project.simulatorRules
+  setSimulatorRule: async (rule: number, enabled: boolean, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('set_simulator_rule', { sessionId, rule, enabled });
+  },
+
+  resetSimulatorCounters: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('reset_simulator_counters', { sessionId });
+  },
+
+  getSimulatorStatus: async (sessionId: string = DEFAULT_SESSION_ID): Promise<SimulatorRuleStatus[]> => {
+    return await invoke<SimulatorRuleStatus[]>('get_simulator_status', { sessionId });
+  },
+
+  // Echo everything sent back as received, for trying things without hardware
+  openLoopback: async (sessionId: string = DEFAULT_SESSION_ID, framing: FramingConfig | null = null): Promise<void> => {
+    await serialService.connect(
+      { portName: 'LOOPBACK', baudRate: 9600, parity: 'None', dataBits: 8, stopBits: 1 },
+      sessionId,
+      null,
+      framing
+    );
+  },
+
+  // Linux only: resolves to the /dev/pts path another program can open to
+  // talk to this session
+  openVirtualPair: async (sessionId: string = DEFAULT_SESSION_ID, framing: FramingConfig | null = null): Promise<string> => {
+    const path = await invoke<string>('open_virtual_pair', { sessionId, framing });
+    await serialService.attachStream(sessionId);
+    return path;
+  },
+
+  // Play a pcapng capture or a termLight log file back as a virtual port;
+  // the line settings are ignored
+  openReplay: async (filePath: string, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await serialService.connect(
+      { portName: `REPLAY:${filePath}`, baudRate: 9600, parity: 'None', dataBits: 8, stopBits: 1 },
+      sessionId
+    );
+  },
+
+  setReplayMode: async (mode: ReplayMode, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('set_replay_mode', { sessionId, mode });
+  },
+
+  stepReplay: async (count: number = 1, sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('step_replay', { sessionId, count });
+  },
+
+  restartReplay: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('restart_replay', { sessionId });
+  },
+
+  getReplayStatus: async (sessionId: string = DEFAULT_SESSION_ID): Promise<ReplayStatus> => {
+    return await invoke<ReplayStatus>('get_replay_status', { sessionId });
+  },
+
+  // Report every occurrence of these receive sequences as a receive-match
+  // event; resolves to how many could be parsed and are being watched
+  startReceiveMatching: async (receiveCommands: ReceiveCommand[], sessionId: string = DEFAULT_SESSION_ID): Promise<number> => {
+    return await invoke<number>('start_receive_matching', { sessionId, receiveCommands });
+  },
+
+  stopReceiveMatching: async (sessionId: string = DEFAULT_SESSION_ID): Promise<void> => {
+    await invoke('stop_receive_matching', { sessionId });
+  },
+
+  listenToReceiveMatches: async (callback: (payload: ReceiveMatchPayload) => void) => {
+    return await listen<ReceiveMatchPayload>('receive-match', (event) => {
+      callback(event.payload);
+    });
+  },
+
+  listenToPortChanges: async (
+    onAdded: (port: PortInfo) => void,
+    onRemoved: (port: PortInfo) => void,
+    onOpenPortRemoved?: (payload: OpenPortRemovedPayload) => void
+  ) => {
+    const unlisteners = await Promise.all([
+      listen<PortInfo>('port-added', (event) => onAdded(event.payload)),
+      listen<PortInfo>('port-removed', (event) => onRemoved(event.payload)),
+      listen<OpenPortRemovedPayload>('open-port-removed', (event) => onOpenPortRemoved?.(event.payload)),
+    ]);
+    return () => unlisteners.forEach((unlisten) => unlisten());
+  },
+
+  listenToReconnectEvents: async (handlers: {
+    onDisconnected?: (payload: DisconnectedPayload) => void;
+    onReconnected?: (payload: ReconnectedPayload) => void;
+    onReconnectFailed?: (payload: ReconnectFailedPayload) => void;
+  }) => {
+    const unlisteners = await Promise.all([
+      listen<DisconnectedPayload>('disconnected', (event) => handlers.onDisconnected?.(event.payload)),
+      listen<ReconnectedPayload>('reconnected', (event) => handlers.onReconnected?.(event.payload)),
+      listen<ReconnectFailedPayload>('reconnect-failed', (event) => handlers.onReconnectFailed?.(event.payload)),
+    ]);
+    return () => unlisteners.forEach((unlisten) => unlisten());
+  },
+
+  listSessions: async (): Promise<SessionInfo[]> => {
+    return await invoke('list_sessions');
+  },
+
+  getConnectionStatus: async (sessionId?: string): Promise<ConnectionStatus[]> => {
+    return await invoke('get_connection_status', { sessionId });
+  },
+
+  listenToConnectionState: async (callback: (payload: ConnectionStatePayload) => void) => {
+    return await listen<ConnectionStatePayload>('connection-state', (event) => callback(event.payload));
+  },
+
+  listenToData: async (callback: (data: Uint8Array, sessionId: string) => void) => {
     console.log('Setting up serial data listener');
-    return await listen<SerialPayload>('serial-payload', (event) => {
-      console.log('Serial data received:', event.payload);
-      callback(new Uint8Array(event.payload.data));
+    return await serialService.listenToRecords((record) => {
+      if (record.direction !== 'RX') return;
+      console.log('Serial data received:', record);
+      callback(new Uint8Array(record.data), record.session_id);
+    });
+  },
+
+  // Both directions, stamped and numbered by the backend, whether they arrive
+  // as events or over a stream
+  listenToRecords: async (callback: RecordListener) => {
+    recordListeners.add(callback);
+    const unlisten = await listen<SerialPayload>('serial-payload', (event) => callback(event.payload));
+    return () => {
+      recordListeners.delete(callback);
+      unlisten();
+    };
+  },
+
+  listenToClientEvents: async (
+    onConnected: (address: string, sessionId: string) => void,
+    onDisconnected: (address: string, sessionId: string) => void
+  ) => {
+    const unlistenConnected = await listen<ClientPayload>('client-connected', (event) => {
+      onConnected(event.payload.address, event.payload.session_id);
+    });
+    const unlistenDisconnected = await listen<ClientPayload>('client-disconnected', (event) => {
+      onDisconnected(event.payload.address, event.payload.session_id);
     });
+    return () => {
+      unlistenConnected();
+      unlistenDisconnected();
+    };
   },
 };
@@ -30,8 +30,8 @@
  */
 
 import { StateCreator } from 'zustand';
-import { SerialConfig, PortInfo } from '../../types';
-import { serialService } from '../../services/serialService';
+import { SerialConfig, PortInfo, FlowControl } from '../../types';
+import { serialService, DEFAULT_SESSION_ID, ConnectionStatePayload } from '../../services/serialService';
 
 export interface SerialSlice {
   isConnected: boolean;
@@ -42,6 +42,7 @@ export interface SerialSlice {
   parity: 'None' | 'Even' | 'Odd';
   dataBits: 5 | 6 | 7 | 8;
   stopBits: 1 | 1.5 | 2;
+  flowControl: FlowControl;
   availablePorts: PortInfo[];
   
   // Actions
@@ -50,6 +51,8 @@ export interface SerialSlice {
   refreshPorts: () => Promise<void>;
   connectPort: () => Promise<void>;
   disconnectPort: () => Promise<void>;
+  syncConnectionStatus: () => Promise<void>;
+  handleConnectionState: (payload: ConnectionStatePayload) => void;
   sendSerialData: (data: Uint8Array) => Promise<void>;
   clearError: () => void;
 }
@@ -63,6 +66,7 @@ export const createSerialSlice: StateCreator<SerialSlice> = (set, get) => ({
   parity: 'None',
   dataBits: 8,
   stopBits: 1,
+  flowControl: 'None',
   availablePorts: [],
 
   setIsConnected: (isConnected) => set({ isConnected }),
@@ -100,6 +104,7 @@ export const createSerialSlice: StateCreator<SerialSlice> = (set, get) => ({
         parity: state.parity,
         dataBits: state.dataBits,
         stopBits: state.stopBits,
+        flowControl: state.flowControl,
       });
       set({ isConnected: true, isConnecting: false });
     } catch (err) {
@@ -121,6 +126,37 @@ export const createSerialSlice: StateCreator<SerialSlice> = (set, get) => ({
     }
   },
 
+  // Pick up a session that outlived a webview reload
+  syncConnectionStatus: async () => {
+    try {
+      const statuses = await serialService.getConnectionStatus();
+      const status = statuses.find((s) => s.session_id === DEFAULT_SESSION_ID);
+      if (status && status.state !== 'closed') {
+        await serialService.attachStream(DEFAULT_SESSION_ID);
+        set({ isConnected: status.is_connected, portName: status.port_name });
+      } else {
+        set({ isConnected: false });
+      }
+    } catch (err) {
+      console.error('Failed to get connection status:', err);
+    }
+  },
+
+  handleConnectionState: (payload) => {
+    if (payload.session_id !== DEFAULT_SESSION_ID) return;
+    switch (payload.state) {
+      case 'open':
+        set({ isConnected: true });
+        break;
+      case 'error':
+        set({ isConnected: false, error: payload.message });
+        break;
+      case 'closed':
+        set({ isConnected: false });
+        break;
+    }
+  },
+
   sendSerialData: async (data: Uint8Array) => {
     if (!get().isConnected) return;
     try {
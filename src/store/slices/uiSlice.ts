@@ -39,6 +39,7 @@ export interface LogEntry {
   timestamp: number;
   direction: 'rx' | 'tx';
   data: number[]; // Store as number[] to avoid Uint8Array serialization issues
+  note?: string; // Shown instead of the data for line conditions such as BREAK
 }
 
 export interface UiSlice {
@@ -39,6 +39,15 @@ export interface ProjectMetadata {
   lastModified: Date;
 }
 
+// Automatic answer used when termLight simulates the device
+export interface SimulatorRule {
+  receiveIndex: number;
+  sendIndex: number;
+  delayMs: number;
+  enabled: boolean;
+  maxAnswers: number; // 0 answers every match
+}
+
 export interface Project {
   metadata: ProjectMetadata;
   serialConfig: SerialConfig;
@@ -48,6 +57,7 @@ export interface Project {
   commSettings: (number | string)[]; // Can be numbers (v7) or mixed (v8)
   commDisplay: number;
   commChannels: string[];
+  simulatorRules?: SimulatorRule[];
 }
 
 
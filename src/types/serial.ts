@@ -30,9 +30,10 @@
  * @date 2025-11-19
  */
 
-export type Parity = 'None' | 'Even' | 'Odd';
+export type Parity = 'None' | 'Even' | 'Odd' | 'Mark' | 'Space';
 export type DataBits = 5 | 6 | 7 | 8;
 export type StopBits = 1 | 1.5 | 2;
+export type FlowControl = 'None' | 'Hardware' | 'Software';
 export type DataFormat = 'Serial Monitor(ASCII)' | 'HEX' | 'DEC' | 'BIN';
 export type InputFormat = 'ASCII' | 'HEX' | 'DEC' | 'BIN';
 export type LineEnding = 'None' | 'NL' | 'CR' | 'Both';
@@ -43,9 +44,23 @@ export interface SerialConfig {
   parity: Parity;
   dataBits: DataBits;
   stopBits: StopBits;
+  flowControl?: FlowControl;
+}
+
+// Parity and stop bit settings the backend can open a port with on this platform
+export interface LineCapabilities {
+  parity: Parity[];
+  stopBits: StopBits[];
 }
 
 export interface PortInfo {
   name: string;
   description?: string;
+  port_type: 'usb' | 'pci' | 'bluetooth' | 'unknown';
+  vid?: number;
+  pid?: number;
+  serial_number?: string;
+  manufacturer?: string;
+  product?: string;
+  by_id_path?: string;
 }
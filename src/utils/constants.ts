@@ -33,7 +33,8 @@
 export const BAUD_RATES = [300, 1200, 2400, 4800, 9600, 19200, 38400, 57600, 115200];
 export const DATA_BITS = [5, 6, 7, 8] as const;
 export const STOP_BITS = [1, 1.5, 2] as const;
-export const PARITY_OPTIONS = ['None', 'Even', 'Odd'] as const;
+export const PARITY_OPTIONS = ['None', 'Even', 'Odd', 'Mark', 'Space'] as const;
+export const FLOW_CONTROL_OPTIONS = ['None', 'Hardware', 'Software'] as const;
 export const APP_VERSION = '0.1.2';
 
 export const THEME_COLORS = {